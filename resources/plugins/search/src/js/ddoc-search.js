@@ -3,8 +3,14 @@
 // Take last version at https://github.com/Canop/ddoc
 ;window.ddoc_search = (function() {
 
-// [{name,href,body}]
+// [{name,href,body}], pages crawled when there's no search index
 const docs = [];
+// [{title,href,sections:[{id,level,title,text}]}], from the search-index.json
+// file built by ddoc next to the js directory
+let index_pages = null;
+let index_loading = null;
+// the URL of this script, to find the search index
+const script_src = document.currentScript ? document.currentScript.src : null;
 const tag_score = {
     HTML: 200,
     H1: 100,
@@ -43,6 +49,28 @@ async function add_doc(name, href) {
     docs.push({name, href, body: doc.body});
 }
 
+// Load the search index built by ddoc, return whether it's available
+async function load_index() {
+    if (!script_src) {
+        return false;
+    }
+    try {
+        const index_url = new URL('../search-index.json', script_src);
+        const response = await fetch(index_url);
+        if (!response.ok) {
+            return false;
+        }
+        const index = await response.json();
+        index_pages = index.pages.map(page => ({
+            ...page,
+            href: new URL(page.href, index_url).href,
+        }));
+        return true;
+    } catch (e) {
+        return false;
+    }
+}
+
 // Add docs from all links in menus matching css_selector
 async function add_menu_docs(css_selector) {
     const menus = document.querySelectorAll(css_selector);
@@ -61,6 +89,9 @@ async function add_menu_docs(css_selector) {
 
 // Return [{doc_idx, score, page, section, href, tag, extract}] of matching docs
 function search_docs({pattern}) {
+    if (index_pages) {
+        return search_index({pattern});
+    }
     let regex = RegExp(`\\b${pattern}`, 'i');
     let matches = [];
     for (let i = 0; i < docs.length; i++) {
@@ -118,6 +149,63 @@ function search_docs({pattern}) {
     return matches;
 }
 
+// Return [{doc_idx, score, page, section, href, tag, extract}] of matching
+// sections of the search index
+function search_index({pattern}) {
+    let regex = RegExp(`\\b${pattern}`, 'i');
+    let matches = [];
+    for (let i = 0; i < index_pages.length; i++) {
+        let page = index_pages[i];
+        let page_score = regex.test(page.title) ? 5 : 0;
+        if (page_score > 0) {
+            matches.push({
+                doc_idx: i,
+                page: page.title,
+                href: page.href,
+                score: tag_score.HTML,
+            });
+        }
+        let text_added = false;
+        for (let section of page.sections) {
+            let hash = section.id ? `#${section.id}` : '';
+            if (section.level && regex.test(section.title)) {
+                matches.push({
+                    doc_idx: i,
+                    page: page.title,
+                    section: section.title,
+                    href: `${page.href}${hash}`,
+                    tag: `H${section.level}`,
+                    score: page_score + tag_score[`H${section.level}`],
+                });
+                continue;
+            }
+            if (text_added || matches.length >= 50) {
+                continue;
+            }
+            let found = regex.exec(section.text);
+            if (found) {
+                let start = Math.max(0, found.index - 100);
+                let extract = section.text.substring(start, start + 400);
+                if (start > 0) {
+                    extract = '…' + extract.replace(/^\S*\s/, '');
+                }
+                matches.push({
+                    doc_idx: i,
+                    page: page.title,
+                    section: section.title,
+                    href: `${page.href}${hash}`,
+                    tag: 'P',
+                    score: page_score + tag_score.P,
+                    extract,
+                });
+                text_added = true;
+            }
+        }
+    }
+    matches.sort((a, b) => b.score - a.score);
+    return matches;
+}
+
 function highlight_if_needed() {
     const url = new URL(window.location);
     const pattern = url.searchParams.get('search');
@@ -348,11 +436,16 @@ async function open(options = {}) {
 }
 
 // options:{
-//  menu_selector: css selector of menu element(s) to pull docs from
+//  menu_selector: css selector of menu element(s) to pull docs from,
+//      when there's no search index
 //  content_selector: css selector of element parent of content to search
 // }
 async function prepare(options = {}) {
-    if (options.menu_selector) {
+    if (!index_loading) {
+        index_loading = load_index();
+    }
+    let has_index = await index_loading;
+    if (!has_index && options.menu_selector) {
         await add_menu_docs(options.menu_selector);
     }
     if (options.content_selector) {
@@ -0,0 +1,267 @@
+use {
+    crate::*,
+    lazy_regex::{
+        regex_captures_iter,
+        regex_replace_all,
+    },
+    rustc_hash::FxHashMap,
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::PathBuf,
+    },
+    xxhash_rust::xxh3::xxh3_64,
+};
+
+/// Name of the file, in the build directory, mapping the original names
+/// of the assets to their fingerprinted names
+pub static ASSET_MANIFEST_FILE_NAME: &str = "asset-manifest.json";
+
+/// Content-hashed names of the static assets (JS, CSS, images), used
+/// instead of the original ones when `fingerprint-assets` is set, so that
+/// the files can be cached forever
+#[derive(Debug, Default)]
+pub struct AssetFingerprints {
+    /// fingerprinted paths, keyed by the original ones, both relative
+    /// to the root of the site (eg `css/main.css` -> `css/main.3f2a9c1b.css`)
+    names: BTreeMap<String, String>,
+    /// content of the CSS files, whose `url()`s and `@import`s were
+    /// rewritten to target the fingerprinted assets
+    css_contents: FxHashMap<String, String>,
+}
+
+impl AssetFingerprints {
+    /// Compute the fingerprints of the given static files, keyed by their
+    /// paths relative to the root of the site
+    pub fn compute(static_files: &FxHashMap<String, PathBuf>) -> DdResult<Self> {
+        let mut fingerprints = Self::default();
+        // CSS files come last, as their content depends on the names of the other assets
+        for (path, src_path) in static_files {
+            if !is_css(path) {
+                let content = fs::read(src_path)?;
+                fingerprints
+                    .names
+                    .insert(path.clone(), fingerprinted_path(path, &content));
+            }
+        }
+        // and a CSS file comes after the ones it imports, unless they import it too
+        let mut css_files = Vec::new();
+        for (path, src_path) in static_files {
+            if is_css(path) {
+                let css = fs::read_to_string(src_path)?;
+                let imports = css_imports(&css, path);
+                css_files.push((path, css, imports));
+            }
+        }
+        while !css_files.is_empty() {
+            let i = css_files
+                .iter()
+                .position(|(_, _, imports)| {
+                    imports
+                        .iter()
+                        .all(|import| !css_files.iter().any(|(path, _, _)| *path == import))
+                })
+                .unwrap_or(0); // import cycle
+            let (path, css, _) = css_files.swap_remove(i);
+            let css = rewrite_css_urls(&css, path, &fingerprints.names);
+            fingerprints
+                .names
+                .insert(path.clone(), fingerprinted_path(path, css.as_bytes()));
+            fingerprints.css_contents.insert(path.clone(), css);
+        }
+        Ok(fingerprints)
+    }
+    /// Add the fingerprint of a generated asset
+    pub fn add(
+        &mut self,
+        path: &str,
+        content: &[u8],
+    ) {
+        self.names
+            .insert(path.to_string(), fingerprinted_path(path, content));
+    }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    /// Return the fingerprinted path of an asset, if there's one
+    pub fn get(
+        &self,
+        path: &str,
+    ) -> Option<&str> {
+        self.names.get(path).map(String::as_str)
+    }
+    /// Return the fingerprinted path of an asset, or the given path if
+    /// it's not a fingerprinted asset
+    pub fn resolve<'s>(
+        &'s self,
+        path: &'s str,
+    ) -> &'s str {
+        self.get(path).unwrap_or(path)
+    }
+    /// Return the content to write for a CSS file, with its urls rewritten
+    pub fn css_content(
+        &self,
+        path: &str,
+    ) -> Option<&str> {
+        self.css_contents.get(path).map(String::as_str)
+    }
+    /// Return the JSON of the asset manifest
+    pub fn manifest_json(&self) -> DdResult<String> {
+        Ok(serde_json::to_string_pretty(&self.names)?)
+    }
+}
+
+fn is_css(path: &str) -> bool {
+    path.ends_with(".css")
+}
+
+/// Insert the hash of the content before the extension of the file name
+/// (`css/main.css` -> `css/main.3f2a9c1b.css`)
+fn fingerprinted_path(
+    path: &str,
+    content: &[u8],
+) -> String {
+    let hash = format!("{:016x}", xxh3_64(content));
+    let hash = &hash[..8];
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = name_start + dot;
+            format!("{}.{hash}{}", &path[..dot], &path[dot..])
+        }
+        _ => format!("{path}.{hash}"),
+    }
+}
+
+/// Return the paths, relative to the site root, of the files imported by
+/// a CSS file (at `css_path`) with `@import` rules
+fn css_imports(
+    css: &str,
+    css_path: &str,
+) -> Vec<String> {
+    let css_dir = css_path.rfind('/').map_or("", |i| &css_path[..i]);
+    regex_captures_iter!(r#"@import\s+(?:url\(\s*)?['"]?([^'")\s?#]+)"#, css)
+        .filter_map(|captures| resolve_relative_url(css_dir, &captures[1]))
+        .collect()
+}
+
+/// Rewrite the relative `url()`s and `@import` URLs of a CSS file (at
+/// `css_path`) which target fingerprinted assets
+fn rewrite_css_urls(
+    css: &str,
+    css_path: &str,
+    names: &BTreeMap<String, String>,
+) -> String {
+    let css_dir = css_path.rfind('/').map_or("", |i| &css_path[..i]);
+    regex_replace_all!(
+        r#"(url\(\s*['"]?|@import\s+['"])([^'")\s]+)"#,
+        css,
+        |all: &str, start: &str, url: &str| {
+            let (url_path, suffix) = url
+                .find(['?', '#'])
+                .map_or((url, ""), |i| (&url[..i], &url[i..]));
+            let Some(target) = resolve_relative_url(css_dir, url_path) else {
+                return all.to_string();
+            };
+            let Some(fingerprinted) = names.get(&target) else {
+                return all.to_string();
+            };
+            // the fingerprinted file is in the same directory, so only
+            // the file name changes
+            let name_start = url_path.rfind('/').map_or(0, |i| i + 1);
+            let new_name = fingerprinted.rsplit('/').next().unwrap_or(fingerprinted);
+            format!("{start}{}{new_name}{suffix}", &url_path[..name_start])
+        }
+    )
+    .to_string()
+}
+
+/// Resolve a relative url from a directory of the site, returning None
+/// for absolute urls and urls going above the root
+pub fn resolve_relative_url(
+    dir: &str,
+    url: &str,
+) -> Option<String> {
+    if url.starts_with('/') || url.contains(':') {
+        return None;
+    }
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for token in url.split('/') {
+        match token {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(token),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[test]
+fn test_fingerprints_and_css_urls() {
+    assert_eq!(
+        fingerprinted_path("css/main.css", b"a").len(),
+        "css/main.12345678.css".len()
+    );
+    assert!(fingerprinted_path("img/x.png", b"a").starts_with("img/x."));
+    assert!(fingerprinted_path("js/.hidden", b"a").starts_with("js/.hidden."));
+    let mut names = BTreeMap::new();
+    names.insert("img/bg.png".to_string(), "img/bg.0badcafe.png".to_string());
+    let css = r#"a{background:url("../img/bg.png?v=2")} b{background:url(https://x.com/img/bg.png)} c{background:url(../img/other.png)}"#;
+    assert_eq!(
+        rewrite_css_urls(css, "css/site.css", &names),
+        r#"a{background:url("../img/bg.0badcafe.png?v=2")} b{background:url(https://x.com/img/bg.png)} c{background:url(../img/other.png)}"#,
+    );
+    names.insert(
+        "css/theme.css".to_string(),
+        "css/theme.0ddba11.css".to_string(),
+    );
+    let css = "@import \"theme.css\";\n@import url('theme.css') screen;\n";
+    assert_eq!(
+        css_imports(css, "css/site.css"),
+        ["css/theme.css", "css/theme.css"]
+    );
+    assert_eq!(
+        rewrite_css_urls(css, "css/site.css", &names),
+        "@import \"theme.0ddba11.css\";\n@import url('theme.0ddba11.css') screen;\n",
+    );
+}
+
+#[test]
+fn test_fingerprints_of_imported_css() {
+    let project = TestProject::new("fingerprints-css-imports");
+    project
+        .write("img/bg.png", "png")
+        .write("css/a.css", "@import \"b.css\";")
+        .write("css/b.css", "@import url(c.css);")
+        .write("css/c.css", "x{background:url(../img/bg.png)}");
+    let static_files: FxHashMap<String, PathBuf> =
+        ["img/bg.png", "css/a.css", "css/b.css", "css/c.css"]
+            .into_iter()
+            .map(|path| (path.to_string(), project.root.join(path)))
+            .collect();
+    let fingerprints = AssetFingerprints::compute(&static_files).unwrap();
+    let name = |path: &str| {
+        let name = fingerprints.get(path).unwrap();
+        name[name.rfind('/').unwrap() + 1..].to_string()
+    };
+    assert!(
+        fingerprints
+            .css_content("css/c.css")
+            .unwrap()
+            .contains(&name("img/bg.png"))
+    );
+    assert!(
+        fingerprints
+            .css_content("css/b.css")
+            .unwrap()
+            .contains(&name("css/c.css"))
+    );
+    assert!(
+        fingerprints
+            .css_content("css/a.css")
+            .unwrap()
+            .contains(&name("css/b.css"))
+    );
+}
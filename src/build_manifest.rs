@@ -0,0 +1,233 @@
+use {
+    crate::*,
+    rustc_hash::FxHashSet,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::Path,
+        time::SystemTime,
+    },
+    xxhash_rust::xxh3::xxh3_64,
+};
+
+/// Name of the file, in the build directory, recording what was written there
+pub static BUILD_MANIFEST_FILE_NAME: &str = ".ddoc-manifest.json";
+
+/// What was written in the build directory, so that a build can skip the
+/// files which didn't change and remove the ones which aren't produced anymore
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// Version of ddoc which wrote the files (the manifest of another
+    /// version is ignored)
+    pub ddoc_version: String,
+    /// The written files, keyed by their path relative to the build directory
+    pub files: BTreeMap<String, FileRecord>,
+    /// The files written or kept since the start of the current build
+    #[serde(skip)]
+    touched: FxHashSet<String>,
+}
+
+/// The inputs and output of a file of the build directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileRecord {
+    /// Fingerprint (size and modification date) of the source file: the
+    /// markdown file of a page, or a static file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Hash of the written content, for generated files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Hash of the inputs of a page other than its markdown file: the
+    /// config, the site map, the static and included files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<String>,
+    /// What was found when rendering a page, still reported when the
+    /// page isn't rendered again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageRecord>,
+}
+
+/// What's known of a page after its rendering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageRecord {
+    /// Size of the HTML file, in bytes
+    pub size: usize,
+    pub heading_count: usize,
+    pub links: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The sections of the page, when there's a search index
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_sections: Vec<SearchSection>,
+}
+
+impl BuildManifest {
+    /// Read the manifest of the build directory, if there's one made by
+    /// this version of ddoc
+    pub fn read(sink: &dyn OutputSink) -> Option<Self> {
+        Self::read_any_version(sink).filter(|manifest| manifest.ddoc_version == DDOC_VERSION)
+    }
+    /// Read the manifest of the build directory, whatever the version of
+    /// ddoc which made it, eg to know which files it wrote
+    pub fn read_any_version(sink: &dyn OutputSink) -> Option<Self> {
+        let json = sink.read(BUILD_MANIFEST_FILE_NAME).ok()??;
+        serde_json::from_slice(&json).ok()
+    }
+    pub fn write(
+        &self,
+        sink: &dyn OutputSink,
+    ) -> DdResult<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        sink.write(BUILD_MANIFEST_FILE_NAME, json.as_bytes())
+    }
+    /// Start a build: files not touched until `prune` will be removed
+    pub fn start_build(&mut self) {
+        self.ddoc_version = DDOC_VERSION.to_string();
+        self.touched.clear();
+    }
+    /// Tell whether the file at `path` (relative to the build directory)
+    /// is up to date, that is has the same content hash for a generated file,
+    /// or the same source for a copied one, and mark it as touched if it is
+    pub fn is_up_to_date(
+        &mut self,
+        sink: &dyn OutputSink,
+        path: &str,
+        record: &FileRecord,
+    ) -> bool {
+        let Some(previous) = self.files.get(path) else {
+            return false;
+        };
+        let unchanged = match &record.hash {
+            Some(hash) => previous.hash.as_ref() == Some(hash),
+            // a copied file must not replace a generated one (eg minified)
+            None => previous.hash.is_none() && previous.source == record.source,
+        };
+        if !unchanged || !sink.exists(path) {
+            return false;
+        }
+        self.record(path.to_string(), record.clone());
+        true
+    }
+    /// Return what was found when rendering the page written at `path`
+    /// (relative to the build directory) if neither its source nor its
+    /// other inputs changed since, marking the file as touched
+    pub fn unchanged_page(
+        &mut self,
+        sink: &dyn OutputSink,
+        path: &str,
+        source: &str,
+        inputs: &str,
+    ) -> Option<PageRecord> {
+        let previous = self.files.get(path)?;
+        if previous.source.as_deref() != Some(source)
+            || previous.inputs.as_deref() != Some(inputs)
+            || !sink.exists(path)
+        {
+            return None;
+        }
+        let page = previous.page.clone()?;
+        self.touched.insert(path.to_string());
+        Some(page)
+    }
+    /// Record a file which was just written
+    pub fn record(
+        &mut self,
+        path: String,
+        record: FileRecord,
+    ) {
+        self.touched.insert(path.clone());
+        self.files.insert(path, record);
+    }
+    /// Remove from the build directory the files of the previous builds
+    /// which weren't touched by the current one, returning their count
+    pub fn prune(
+        &mut self,
+        sink: &dyn OutputSink,
+    ) -> DdResult<usize> {
+        let stale: Vec<String> = self
+            .files
+            .keys()
+            .filter(|path| !self.touched.contains(*path))
+            .cloned()
+            .collect();
+        for path in &stale {
+            self.files.remove(path);
+            sink.remove(path)?;
+        }
+        Ok(stale.len())
+    }
+}
+
+impl FileRecord {
+    /// The record of a file copied from `src_path`
+    pub fn copied(src_path: &Path) -> DdResult<Self> {
+        let metadata = fs::metadata(src_path)?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(Self {
+            source: Some(format!("{}-{}", metadata.len(), mtime.as_nanos())),
+            hash: None,
+            inputs: None,
+            page: None,
+        })
+    }
+    /// The record of a file generated with the given content
+    pub fn generated(content: &[u8]) -> Self {
+        Self {
+            source: None,
+            hash: Some(format!("{:016x}", xxh3_64(content))),
+            inputs: None,
+            page: None,
+        }
+    }
+    /// Add the fingerprint of the source file, if it can be read
+    pub fn with_source(
+        mut self,
+        src_path: &Path,
+    ) -> Self {
+        self.source = Self::source_fingerprint(src_path);
+        self
+    }
+    /// Add what's needed to skip the rendering of an unchanged page
+    pub fn with_page(
+        mut self,
+        inputs: &str,
+        page: PageRecord,
+    ) -> Self {
+        self.inputs = Some(inputs.to_string());
+        self.page = Some(page);
+        self
+    }
+    /// The fingerprint (size and modification date) of a source file,
+    /// if it can be read
+    pub fn source_fingerprint(src_path: &Path) -> Option<String> {
+        Self::copied(src_path).ok().and_then(|r| r.source)
+    }
+}
+
+#[test]
+fn test_build_manifest_prune() {
+    let test_project = TestProject::new("manifest");
+    let build_path = test_project.root.join("site");
+    let sink = FsSink::new(&build_path);
+    let mut manifest = BuildManifest::default();
+    for path in ["a/index.html", "b/c/index.html"] {
+        let file_path = build_path.join(path);
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(&file_path, path).unwrap();
+        manifest.record(path.to_string(), FileRecord::generated(path.as_bytes()));
+    }
+    manifest.start_build();
+    let record = FileRecord::generated(b"a/index.html");
+    assert!(manifest.is_up_to_date(&sink, "a/index.html", &record));
+    let record = FileRecord::generated(b"changed");
+    assert!(!manifest.is_up_to_date(&sink, "b/c/index.html", &record));
+    assert_eq!(manifest.prune(&sink).unwrap(), 1);
+    assert!(build_path.join("a/index.html").exists());
+    assert!(!build_path.join("b").exists());
+}
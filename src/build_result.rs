@@ -0,0 +1,113 @@
+use {
+    crate::*,
+    serde::Serialize,
+    std::{
+        fs,
+        path::Path,
+        time::Duration,
+    },
+};
+
+/// What a build of the site produced
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct BuildResult {
+    /// Paths of the files of the site, relative to its root, sorted, including
+    /// the ones which were already up to date
+    pub files: Vec<String>,
+    /// The rendered pages, in the order of the site map
+    pub pages: Vec<PageReport>,
+    /// Paths, relative to the site root, of the static files, bundles
+    /// and extra statics, sorted
+    pub assets: Vec<String>,
+    /// Names of the plugins of the project
+    pub plugins: Vec<String>,
+    /// Problems found while loading the project, then while rendering
+    /// the pages, in the order of the site map
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// What was rendered for a page
+#[derive(Debug, Clone, Serialize)]
+pub struct PageReport {
+    pub path: PagePath,
+    /// Path of the HTML file, relative to the site root
+    pub file: String,
+    pub title: String,
+    /// Size of the HTML file, in bytes
+    pub size: usize,
+    #[serde(rename = "render_time_ms", serialize_with = "serialize_millis")]
+    pub render_time: Duration,
+    pub heading_count: usize,
+    /// Targets of the links of the Markdown, as written in the HTML
+    pub links: Vec<String>,
+}
+
+impl BuildResult {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+    /// Print the diagnostics to stderr, with paths relative to `root`
+    pub fn print_diagnostics(
+        &self,
+        root: &Path,
+    ) {
+        for diagnostic in &self.diagnostics {
+            diagnostic.print(root);
+        }
+    }
+    /// Build the JSON report of the build, with the paths of diagnostic
+    /// locations relative to `root`
+    ///
+    /// # Errors
+    /// Return `DdError::Json` if the serialization fails
+    pub fn report_json(
+        &self,
+        root: &Path,
+    ) -> DdResult<String> {
+        let mut result = self.clone();
+        for diagnostic in &mut result.diagnostics {
+            if let Some(location) = &mut diagnostic.location
+                && let Ok(path) = location.path.strip_prefix(root)
+            {
+                location.path = path.to_path_buf();
+            }
+        }
+        let error_count = result.diagnostics.iter().filter(|d| d.is_error()).count();
+        let report = Report {
+            ddoc_version: env!("CARGO_PKG_VERSION"),
+            error_count,
+            warning_count: result.diagnostics.len() - error_count,
+            result: &result,
+        };
+        Ok(serde_json::to_string_pretty(&report)?)
+    }
+    /// Write the JSON report of the build in the file at `path`
+    ///
+    /// # Errors
+    /// Return `DdError::Io` if the file can't be written
+    pub fn write_report(
+        &self,
+        path: &Path,
+        root: &Path,
+    ) -> DdResult<()> {
+        fs::write(path, self.report_json(root)?)?;
+        Ok(())
+    }
+}
+
+/// The content of the JSON report
+#[derive(Serialize)]
+struct Report<'r> {
+    ddoc_version: &'static str,
+    error_count: usize,
+    warning_count: usize,
+    #[serde(flatten)]
+    result: &'r BuildResult,
+}
+
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,167 @@
+use {
+    crate::*,
+    lazy_regex::{
+        regex,
+        regex_replace_all,
+    },
+    std::{
+        fs,
+        path::Path,
+    },
+};
+
+/// Path of the CSS bundle, relative to the site root
+pub static CSS_BUNDLE_PATH: &str = "css/ddoc-bundle.css";
+
+/// Path of the JS bundle, relative to the site root
+pub static JS_BUNDLE_PATH: &str = "js/ddoc-bundle.js";
+
+/// A file made of the concatenation of the CSS or JS files of all modules,
+/// linked from the pages instead of those files when `bundle-assets` is set
+#[derive(Debug)]
+pub struct Bundle {
+    /// Path relative to the site root, before fingerprinting
+    pub path: &'static str,
+    pub content: String,
+    /// Most recent modification time of the bundled files, in secs since UNIX_EPOCH
+    pub mtime: u64,
+}
+
+impl Bundle {
+    /// Concatenate the given CSS files, in the order of the links they replace,
+    /// so that the cascade is unchanged
+    ///
+    /// Relative `url()`s of files not directly in the css directory are
+    /// rebased so that they still target the same files.
+    ///
+    /// As they're ignored when not at the start of a style sheet, the `@import`
+    /// rules are moved to the start of the bundle (so the imported rules come
+    /// before all bundled ones), and the `@charset` ones are removed (the
+    /// bundle being UTF-8).
+    pub fn css<'e>(
+        entries: impl Iterator<Item = &'e StaticEntry>,
+        root: &Path,
+        fingerprints: &AssetFingerprints,
+    ) -> DdResult<Self> {
+        let mut bundle = Self::new(CSS_BUNDLE_PATH);
+        let mut imports = String::new();
+        for e in entries {
+            let css = match fingerprints.css_content(&e.served_path) {
+                Some(css) => css.to_string(),
+                None => fs::read_to_string(&e.src_path)?,
+            };
+            let css = rebase_css_urls(&css, &e.served_path, "css");
+            let css = hoist_css_imports(&css, &mut imports);
+            bundle.add(e, root, &css);
+        }
+        bundle.content.insert_str(0, &imports);
+        Ok(bundle)
+    }
+    /// Concatenate the given JS files, in the order of the scripts they replace
+    ///
+    /// Scripts locating files from their own URL (`document.currentScript.src`)
+    /// get the URL of the bundle, which is in the same `js` directory as the
+    /// scripts of the modules, but not as the ones of its subdirectories.
+    pub fn js<'e>(
+        entries: impl Iterator<Item = &'e StaticEntry>,
+        root: &Path,
+    ) -> DdResult<Self> {
+        let mut bundle = Self::new(JS_BUNDLE_PATH);
+        for e in entries {
+            let js = fs::read_to_string(&e.src_path)?;
+            // the semicolon protects against files not ending their last statement
+            bundle.add(e, root, &js);
+            bundle.content.push_str(";\n");
+        }
+        Ok(bundle)
+    }
+    fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            content: String::new(),
+            mtime: 0,
+        }
+    }
+    fn add(
+        &mut self,
+        e: &StaticEntry,
+        root: &Path,
+        content: &str,
+    ) {
+        let src_path = e.src_path.strip_prefix(root).unwrap_or(&e.src_path);
+        self.content
+            .push_str(&format!("/* from {} */\n", src_path.to_string_lossy()));
+        self.content.push_str(content);
+        if !content.ends_with('\n') {
+            self.content.push('\n');
+        }
+        self.mtime = self.mtime.max(e.mtime);
+    }
+}
+
+/// Rewrite the relative `url()`s and `@import` URLs of a CSS file served
+/// at `css_path` so that they're correct from the `dir` directory
+fn rebase_css_urls(
+    css: &str,
+    css_path: &str,
+    dir: &str,
+) -> String {
+    let css_dir = css_path.rfind('/').map_or("", |i| &css_path[..i]);
+    if css_dir == dir {
+        return css.to_string();
+    }
+    regex_replace_all!(
+        r#"(url\(\s*['"]?|@import\s+['"])([^'")\s]+)"#,
+        css,
+        |all: &str, start: &str, url: &str| {
+            let Some(target) = resolve_relative_url(css_dir, url) else {
+                return all.to_string();
+            };
+            let mut new_url = String::new();
+            for _ in dir.split('/').filter(|p| !p.is_empty()) {
+                new_url.push_str("../");
+            }
+            new_url.push_str(&target);
+            format!("{start}{new_url}")
+        }
+    )
+    .to_string()
+}
+
+/// Remove the `@charset` and `@import` rules of a CSS file, pushing the
+/// `@import` ones to `imports`
+fn hoist_css_imports(
+    css: &str,
+    imports: &mut String,
+) -> String {
+    let css = regex_replace_all!(r#"^\s*@charset\s+"[^"]*"\s*;[ \t]*\n?"#, css, "");
+    let import_regex = regex!(r#"(?m)^[ \t]*@import\s[^;]*;[ \t]*\n?"#);
+    for import in import_regex.find_iter(&css) {
+        imports.push_str(import.as_str().trim());
+        imports.push('\n');
+    }
+    import_regex.replace_all(&css, "").to_string()
+}
+
+#[test]
+fn test_rebase_css_urls() {
+    let css =
+        r#"a{background:url("../../img/a.png")} b{background:url(data:image/png;base64,AAA)}"#;
+    assert_eq!(
+        rebase_css_urls(css, "css/theme/dark.css", "css"),
+        r#"a{background:url("../img/a.png")} b{background:url(data:image/png;base64,AAA)}"#,
+    );
+    assert_eq!(rebase_css_urls(css, "css/main.css", "css"), css);
+}
+
+#[test]
+fn test_hoist_css_imports() {
+    let css = "@charset \"UTF-8\";\n@import \"../fonts.css\" screen;\n@import url(x.css);\na{color:red}\n";
+    let css = rebase_css_urls(css, "css/theme/dark.css", "css");
+    let mut imports = String::new();
+    assert_eq!(hoist_css_imports(&css, &mut imports), "a{color:red}\n");
+    assert_eq!(
+        imports,
+        "@import \"../css/fonts.css\" screen;\n@import url(../css/theme/x.css);\n"
+    );
+}
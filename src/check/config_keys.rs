@@ -0,0 +1,106 @@
+use {
+    crate::*,
+    serde_json::{
+        Map,
+        Value,
+    },
+    std::path::Path,
+};
+
+/// Check the keys of a config file against the known properties and
+/// attributes, returning a warning for each unknown one.
+///
+/// Parse errors aren't reported here, as they prevent loading the config
+/// and are handled separately.
+pub fn unknown_key_diagnostics(config_path: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    // read as a map, as a braceless Hjson root can't be read as any value
+    let Ok(map) = read_file::<Map<String, Value>, _>(config_path) else {
+        return diagnostics;
+    };
+    let mut checker = KeyChecker {
+        config_path,
+        diagnostics: &mut diagnostics,
+    };
+    let known_keys = Config::keys();
+    for (key, value) in &map {
+        if !known_keys.contains(key) {
+            checker.warn(&[key], format!("unknown property '{key}'"));
+            continue;
+        }
+        if key == "body"
+            && let Value::Object(elements) = value
+        {
+            checker.check_elements(&mut vec![key], elements);
+        }
+        if key == "hooks"
+            && let Value::Object(hooks) = value
+        {
+            for hook_key in hooks.keys() {
+                if !serde_field_names::<Hooks>().contains(&hook_key.as_str()) {
+                    checker.warn(&[key, hook_key], format!("unknown hook '{hook_key}'"));
+                }
+            }
+        }
+    }
+    diagnostics.sort_by_key(|d| d.location.as_ref().and_then(|l| l.line));
+    diagnostics
+}
+
+struct KeyChecker<'c> {
+    config_path: &'c Path,
+    diagnostics: &'c mut Vec<Diagnostic>,
+}
+
+impl KeyChecker<'_> {
+    fn warn(
+        &mut self,
+        tokens: &[&str],
+        message: String,
+    ) {
+        let mut diagnostic = Diagnostic::warning(message);
+        if let Some(location) = locate_in_file(self.config_path, tokens) {
+            diagnostic = diagnostic.at(location);
+        }
+        self.diagnostics.push(diagnostic);
+    }
+    fn check_elements<'v>(
+        &mut self,
+        tokens: &mut Vec<&'v str>,
+        elements: &'v Map<String, Value>,
+    ) {
+        for (key, value) in elements {
+            let Value::Object(map) = value else {
+                continue; // an attribute of the parent element
+            };
+            let Ok(element_key) = key.parse::<ElementKey>() else {
+                continue; // invalid keys prevent loading the config
+            };
+            tokens.push(key);
+            let known_attributes = match element_key.etype {
+                ElementType::HtmlTag(_) => {
+                    self.check_elements(tokens, map);
+                    tokens.pop();
+                    continue;
+                }
+                ElementType::Link => NavLink::ATTRIBUTES,
+                ElementType::Include => Include::ATTRIBUTES,
+                ElementType::ChildPages => ChildPages::ATTRIBUTES,
+                ElementType::Menu => Menu::ATTRIBUTES,
+                ElementType::Toc => Toc::ATTRIBUTES,
+                ElementType::Main | ElementType::PageTitle => &[],
+            };
+            for attribute in map.keys() {
+                if !known_attributes.iter().any(|known| known.name == attribute) {
+                    tokens.push(attribute);
+                    self.warn(
+                        tokens,
+                        format!("unknown attribute '{}' in {}", attribute, element_key.etype),
+                    );
+                    tokens.pop();
+                }
+            }
+            tokens.pop();
+        }
+    }
+}
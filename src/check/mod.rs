@@ -0,0 +1,351 @@
+//! Verification of a whole project, without building it
+mod config_keys;
+mod unused_content;
+
+pub use config_keys::*;
+
+use {
+    crate::*,
+    pulldown_cmark::{
+        self as pcm,
+        Event,
+        Parser,
+        Tag,
+    },
+    std::{
+        fs,
+        path::Path,
+    },
+};
+
+/// Check the ddoc project at `root` without building it: config files,
+/// plugins, pages, links, images, and included files.
+pub fn check_project(
+    root: &Path,
+    dir_overrides: DirOverrides,
+) -> Vec<Diagnostic> {
+    match ProjectBuilder::new(root).dirs(dir_overrides).load() {
+        Ok(project) => {
+            let mut diagnostics = project.load_diagnostics.clone();
+            diagnostics.extend(project.check());
+            diagnostics
+        }
+        Err(DdError::ConfigNotFound) => {
+            vec![
+                Diagnostic::error(format!("no {} found", CONFIG_FILE_NAMES.join(" or ")))
+                    .at(Location::new(root))
+                    .with_help("you can initialize ddoc with ddoc --init"),
+            ]
+        }
+        Err(e @ DdError::MultipleConfigFiles { .. }) => {
+            vec![config_error_diagnostic(root, e).with_help("keep only one config file")]
+        }
+        Err(e) => vec![load_error_diagnostic(root, e)],
+    }
+}
+
+/// Build the diagnostic for an error preventing the project to load,
+/// locating it in the faulty config file when possible.
+fn load_error_diagnostic(
+    root: &Path,
+    error: DdError,
+) -> Diagnostic {
+    let main_config_path = match Config::path_in_dir(root) {
+        Ok(Some(path)) => path,
+        Ok(None) => return Diagnostic::error(format!("failed to load the project: {error}")),
+        Err(e) => return config_error_diagnostic(root, e),
+    };
+    let main_config = match read_file::<Config, _>(&main_config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            return config_error_diagnostic(&main_config_path, e);
+        }
+    };
+    for name in &main_config.active_plugins {
+        let plugin_root = root.join("plugins").join(name);
+        let plugin_config_path = match Config::path_in_dir(&plugin_root) {
+            Ok(Some(path)) => path,
+            Ok(None) => continue,
+            Err(e) => return config_error_diagnostic(&plugin_root, e),
+        };
+        if let Err(e) = read_file::<Config, _>(&plugin_config_path) {
+            return config_error_diagnostic(&plugin_config_path, e);
+        }
+    }
+    Diagnostic::error(format!("failed to load the project: {error}"))
+}
+
+/// Build the diagnostic for an error met reading the config file at `path`
+fn config_error_diagnostic(
+    path: &Path,
+    error: DdError,
+) -> Diagnostic {
+    let mut location = Location::new(path);
+    match &error {
+        DdError::Hjson(
+            deser_hjson::Error::Syntax { line, col, .. }
+            | deser_hjson::Error::Serde { line, col, .. },
+        ) => {
+            location = location.with_position(*line, *col);
+        }
+        DdError::Json(e) => {
+            location = location.with_position(e.line(), e.column());
+        }
+        DdError::Toml(e) => {
+            if let (Some(span), Ok(text)) = (e.span(), fs::read_to_string(path)) {
+                location = Location::at_offset(path, &text, span.start);
+            }
+        }
+        _ => {}
+    }
+    let message = match error {
+        DdError::Hjson(
+            deser_hjson::Error::Serde { message, .. } | deser_hjson::Error::RawSerde(message),
+        ) => format!("invalid config: {message}"),
+        DdError::Toml(e) => format!("invalid config: {}", e.message()),
+        e @ DdError::MultipleConfigFiles { .. } => e.to_string(),
+        e => format!("invalid config: {e}"),
+    };
+    Diagnostic::error(message).at(location)
+}
+
+/// Locate in a text file the value or key at the end of the given path of tokens,
+/// looking for each token after the position of the previous one.
+///
+/// This is a heuristic, which doesn't need the file to be valid.
+pub fn locate_in_file(
+    path: &Path,
+    tokens: &[&str],
+) -> Option<Location> {
+    let text = fs::read_to_string(path).ok()?;
+    let mut offset = 0;
+    let mut found = None;
+    for token in tokens {
+        let Some(token_offset) = find_token(&text[offset..], token) else {
+            break;
+        };
+        offset += token_offset;
+        found = Some(offset);
+        offset += token.len();
+    }
+    found.map(|offset| Location::at_offset(path, &text, offset))
+}
+
+/// Find a token in a config text, as a whole key or value
+fn find_token(
+    text: &str,
+    token: &str,
+) -> Option<usize> {
+    let is_separator = |c: char| c.is_whitespace() || "\"'{}[],:=".contains(c);
+    let mut start = 0;
+    while let Some(i) = text[start..].find(token) {
+        let i = start + i;
+        let end = i + token.len();
+        let before_ok = text[..i].chars().next_back().is_none_or(is_separator);
+        let after_ok = text[end..].chars().next().is_none_or(is_separator);
+        if before_ok && after_ok {
+            return Some(i);
+        }
+        start = end;
+    }
+    None
+}
+
+impl Project {
+    /// Check the pages and the body configuration of a loaded project,
+    /// returning the problems found.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for page_path in self.nav().order() {
+            if let Some(page) = self.pages.get(page_path) {
+                self.check_page(page, &mut diagnostics);
+            }
+        }
+        self.check_body(&mut diagnostics);
+        self.check_extra_statics(&mut diagnostics);
+        self.check_unused_content(&mut diagnostics);
+        diagnostics
+    }
+
+    fn check_extra_statics(
+        &self,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for extra_static in &self.config.extra_statics {
+            let mut files = Vec::new();
+            let diagnostic = match extra_static.list_files(&self.src_path, &mut files) {
+                Err(e) => Diagnostic::error(format!("invalid extra static: {e}")),
+                Ok(()) if !self.src_path.join(extra_static.rel_path()).exists() => {
+                    Diagnostic::warning(format!(
+                        "extra static {:?} not found in the src directory",
+                        extra_static.path,
+                    ))
+                }
+                Ok(()) => continue,
+            };
+            let location = self.locate_in_config(&["extra-statics", &extra_static.path]);
+            diagnostics.push(match location {
+                Some(location) => diagnostic.at(location),
+                None => diagnostic,
+            });
+        }
+    }
+
+    fn check_page(
+        &self,
+        page: &Page,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Ok(md) = fs::read_to_string(&page.md_file_path) else {
+            let mut diagnostic = Diagnostic::error(format!(
+                "page file {:?} not found",
+                page.md_file_path
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&page.md_file_path),
+            ));
+            let page_path = page.page_path.to_string();
+            if let Some(location) = self.locate_in_config(&["site-map", &page_path]) {
+                diagnostic = diagnostic.at(location);
+            }
+            diagnostics.push(diagnostic);
+            return;
+        };
+        for (event, range) in Parser::new_ext(&md, pcm::Options::all()).into_offset_iter() {
+            let location = || Location::at_offset(&page.md_file_path, &md, range.start);
+            match event {
+                Event::Start(Tag::Link { dest_url, .. })
+                    if self.is_broken_link(&dest_url, &page.page_path) =>
+                {
+                    diagnostics
+                        .push(Diagnostic::error(format!("broken link: {dest_url}")).at(location()));
+                }
+                Event::Start(Tag::Image { dest_url, .. })
+                    if self.is_broken_img(&dest_url, &page.page_path) =>
+                {
+                    diagnostics.push(
+                        Diagnostic::error(format!("broken image: {dest_url}")).at(location()),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_body(
+        &self,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let root_page_path = PagePath::from_path_file("", "index");
+        let mut problems = Vec::new();
+        if let Some(favicon) = self.config.favicon()
+            && self.is_broken_img(favicon, &root_page_path)
+        {
+            problems.push((favicon.to_string(), "broken favicon"));
+        }
+        self.config
+            .body
+            .visit(&mut |element| match &element.content {
+                ElementContent::Link(link) => {
+                    if let Some(href) = &link.href
+                        && href.starts_with('/')
+                        && self.is_broken_link(href, &root_page_path)
+                    {
+                        problems.push((href.clone(), "broken link"));
+                    }
+                    for part in &link.content {
+                        match part {
+                            NavLinkPart::Img { src, .. } => {
+                                if self.is_broken_img(src, &root_page_path) {
+                                    problems.push((src.clone(), "broken image"));
+                                }
+                            }
+                            NavLinkPart::InlineImg { src } => {
+                                if !self.static_source_exists(src) {
+                                    problems.push((src.clone(), "inlined file not found"));
+                                }
+                            }
+                            NavLinkPart::Label(_) => {}
+                        }
+                    }
+                }
+                ElementContent::DomLeaf { attributes, .. }
+                | ElementContent::DomTree { attributes, .. } => {
+                    if let Some(src) = attributes.get("src").and_then(AttributeValue::as_str)
+                        && self.is_broken_img(src, &root_page_path)
+                    {
+                        problems.push((src.to_string(), "broken image"));
+                    }
+                }
+                ElementContent::Include(include) => match include.path(&self.src_path) {
+                    Some(path) if path.exists() => {}
+                    Some(_) => problems.push((include.src.clone(), "included file not found")),
+                    None => problems.push((
+                        include.src.clone(),
+                        "included file outside of the src directory",
+                    )),
+                },
+                _ => {}
+            });
+        for (value, problem) in problems {
+            let mut diagnostic = Diagnostic::error(format!("{problem}: {value}"));
+            if let Some(location) = self.locate_in_configs(&value) {
+                diagnostic = diagnostic.at(location);
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Locate a value found while rendering a page: in the Markdown
+    /// of the page if it's there, else in the body of the configs
+    pub fn locate_in_page(
+        &self,
+        page_path: &PagePath,
+        value: &str,
+    ) -> Option<Location> {
+        self.pages
+            .get(page_path)
+            .and_then(|page| {
+                let md = fs::read_to_string(&page.md_file_path).ok()?;
+                let offset = md.find(value)?;
+                Some(Location::at_offset(&page.md_file_path, &md, offset))
+            })
+            .or_else(|| self.locate_in_configs(value))
+    }
+
+    /// Locate a value of the body, which may come from the main config or from a plugin
+    pub fn locate_in_configs(
+        &self,
+        value: &str,
+    ) -> Option<Location> {
+        self.modules()
+            .iter()
+            .filter_map(|module| module.config.as_ref())
+            .find_map(|config| locate_in_file(config.src(), &[value]))
+    }
+
+    /// Tell whether a link is an internal link to a page which isn't in the site
+    fn is_broken_link(
+        &self,
+        src: &str,
+        page_path: &PagePath,
+    ) -> bool {
+        self.internal_link_target(src, page_path)
+            .is_some_and(|target| !self.pages.contains_key(&target))
+    }
+
+    /// Tell whether an image source is a relative path to a missing image
+    fn is_broken_img(
+        &self,
+        src: &str,
+        page_path: &PagePath,
+    ) -> bool {
+        // unresolved expansions are ignored, as when rendering
+        let Some((up, img_path)) = self.img_src_path(src) else {
+            return false;
+        };
+        if page_path.depth() < up {
+            return true; // going above the site root
+        }
+        !self.static_source_exists(&img_path)
+    }
+}
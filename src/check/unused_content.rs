@@ -0,0 +1,218 @@
+use {
+    crate::*,
+    pulldown_cmark::{
+        self as pcm,
+        Event,
+        Parser,
+        Tag,
+    },
+    rustc_hash::FxHashSet,
+    std::{
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// Extensions of the files which may refer to images
+static TEXT_EXTENSIONS: &[&str] = &[
+    "css",
+    "hjson",
+    "html",
+    "js",
+    "json",
+    "md",
+    "svg",
+    "toml",
+    "txt",
+    "webmanifest",
+    "xml",
+];
+
+impl Project {
+    /// Warn about content which is never shown: Markdown files not in the
+    /// site map, images not referenced, pages listed twice in the site map,
+    /// and pages no other page links to.
+    pub fn check_unused_content(
+        &self,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        self.check_duplicate_pages(diagnostics);
+        let src_files = self.list_src_files();
+        self.check_orphan_md_files(&src_files, diagnostics);
+        self.check_unreferenced_images(&src_files, diagnostics);
+        self.check_unlinked_pages(diagnostics);
+    }
+
+    /// List the files of the src directory, with their path relative to it
+    fn list_src_files(&self) -> Vec<(String, PathBuf)> {
+        let mut files = Vec::new();
+        if self.src_path.is_dir() {
+            let _ = list_normal_files_recursive(&self.src_path, "", &mut files);
+        }
+        files
+    }
+
+    fn check_duplicate_pages(
+        &self,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for (title, page_path) in self.nav().duplicates() {
+            let mut diagnostic =
+                Diagnostic::warning(format!("page listed twice in the site map: {page_path}"))
+                    .with_help("only the first entry is used for the navigation");
+            if let Some(location) = self.locate_in_config(&["site-map", title]) {
+                diagnostic = diagnostic.at(location);
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Warn about the Markdown files which are neither pages nor included
+    fn check_orphan_md_files(
+        &self,
+        src_files: &[(String, PathBuf)],
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let page_files: FxHashSet<&PathBuf> =
+            self.pages.values().map(|page| &page.md_file_path).collect();
+        for (rel_path, path) in src_files {
+            if !rel_path.ends_with(".md")
+                || page_files.contains(path)
+                || self.config.body.includes(rel_path)
+                || self.is_extra_static(rel_path)
+            {
+                continue;
+            }
+            diagnostics.push(
+                Diagnostic::warning("Markdown file not in the site map")
+                    .at(Location::new(path))
+                    .with_help("add it to the site-map, or remove it"),
+            );
+        }
+    }
+
+    /// Warn about the images of the `img` directory whose path appears
+    /// in no page, config, or static file
+    ///
+    /// This is a heuristic: an image is considered referenced as soon as
+    /// its path (eg `img/logo.png`) is found in one of those files.
+    fn check_unreferenced_images(
+        &self,
+        src_files: &[(String, PathBuf)],
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let mut texts = Vec::new();
+        for module in self.modules() {
+            if let Some(config) = &module.config
+                && let Ok(text) = fs::read_to_string(config.src())
+            {
+                texts.push(text);
+            }
+            let mut files = Vec::new();
+            if module.is_main() {
+                files.extend_from_slice(src_files);
+            } else if module.src_path.is_dir() {
+                let _ = list_normal_files_recursive(&module.src_path, "", &mut files);
+            }
+            for (rel_path, path) in files {
+                let is_text = rel_path
+                    .rsplit_once('.')
+                    .is_some_and(|(_, ext)| TEXT_EXTENSIONS.contains(&ext));
+                if is_text && let Ok(text) = fs::read_to_string(path) {
+                    texts.push(text);
+                }
+            }
+        }
+        for (rel_path, path) in src_files {
+            if !rel_path.starts_with("img/")
+                || texts.iter().any(|text| text.contains(rel_path.as_str()))
+            {
+                continue;
+            }
+            diagnostics.push(
+                Diagnostic::warning("image referenced nowhere")
+                    .at(Location::new(path))
+                    .with_help("remove it if it's not needed"),
+            );
+        }
+    }
+
+    /// Warn about the pages which no other page links to, and which
+    /// aren't reachable with the menu or page lists either
+    fn check_unlinked_pages(
+        &self,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let mut has_menu = false;
+        let mut has_page_list = false;
+        let mut linked = FxHashSet::default();
+        let root_page_path = PagePath::from_path_file("", "index");
+        self.config
+            .body
+            .visit(&mut |element| match &element.content {
+                ElementContent::Menu(_) => has_menu = true,
+                ElementContent::ChildPages(_) => has_page_list = true,
+                ElementContent::Link(link) => {
+                    if let Some(href) = &link.href
+                        && let Some(target) = self.internal_link_target(href, &root_page_path)
+                    {
+                        linked.insert(target);
+                    }
+                }
+                _ => {}
+            });
+        if has_menu {
+            return;
+        }
+        for page_path in self.nav().order() {
+            let Some(page) = self.pages.get(page_path) else {
+                continue;
+            };
+            let Ok(md) = fs::read_to_string(&page.md_file_path) else {
+                continue;
+            };
+            for event in Parser::new_ext(&md, pcm::Options::all()) {
+                if let Event::Start(Tag::Link { dest_url, .. }) = event
+                    && let Some(target) = self.internal_link_target(&dest_url, page_path)
+                    && &target != page_path
+                {
+                    linked.insert(target);
+                }
+            }
+        }
+        for page_path in self.nav().order() {
+            if page_path.is_root_index() || linked.contains(page_path) {
+                continue;
+            }
+            if has_page_list && self.nav().section_pages(page_path).nth(1).is_some() {
+                continue; // listed by the page list of the other pages of the section
+            }
+            let mut diagnostic =
+                Diagnostic::warning(format!("page linked from no other page: {page_path}"));
+            let md_path = page_path.md_path_buf(Path::new(""));
+            let md_path = md_path.to_string_lossy();
+            if let Some(location) = self.locate_in_config(&["site-map", &md_path]) {
+                diagnostic = diagnostic.at(location);
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    /// Tell whether a file, given by its path relative to the src
+    /// directory, is copied as an extra static
+    fn is_extra_static(
+        &self,
+        rel_path: &str,
+    ) -> bool {
+        self.config.extra_statics.iter().any(|extra_static| {
+            let static_path = extra_static.rel_path();
+            rel_path == static_path
+                || rel_path
+                    .strip_prefix(static_path)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
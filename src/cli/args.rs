@@ -1,7 +1,9 @@
 use {
+    crate::*,
     clap::{
         CommandFactory,
         Parser,
+        ValueEnum,
     },
     std::path::{
         Path,
@@ -35,6 +37,10 @@ pub struct Args {
     #[arg(long)]
     pub version: bool,
 
+    /// Print the JSON Schema of the ddoc.hjson configuration file
+    #[arg(long)]
+    pub config_schema: bool,
+
     /// Initialize a ddoc project in the specified directory
     #[arg(long)]
     pub init: bool,
@@ -48,6 +54,38 @@ pub struct Args {
     #[arg(long)]
     pub init_plugin: Option<String>,
 
+    /// Check the project (config, plugins, pages, links, images) without
+    /// building it, and exit with a non zero code if any problem is found
+    #[arg(long)]
+    pub check: bool,
+
+    /// Minify the generated HTML pages and the CSS and JS files
+    #[arg(long)]
+    pub minify: bool,
+
+    /// Directory of the pages and static files, instead of the
+    /// `src-dir` of the config
+    #[arg(long)]
+    pub src: Option<PathBuf>,
+
+    /// Directory where the site is built, instead of the `build-dir`
+    /// of the config
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Write a JSON report of the build (pages, assets, plugins,
+    /// errors and warnings) in this file
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    /// Package the pages in a single file, instead of building the site
+    #[arg(long, value_name = "FORMAT")]
+    pub export: Option<ExportFormat>,
+
+    /// Make warnings fail the build or the check, not just errors
+    #[arg(long)]
+    pub strict: bool,
+
     /// Serve files (for local development)
     #[arg(long)]
     pub serve: bool,
@@ -59,6 +97,13 @@ pub struct Args {
     pub path: Option<PathBuf>,
 }
 
+/// Formats of `--export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// An EPUB 3 e-book
+    Epub,
+}
+
 impl Args {
     pub fn print_help(&self) {
         let printer = clap_help::Printer::new(Args::command())
@@ -109,4 +154,15 @@ impl Args {
         };
         Some(project_path)
     }
+    /// Return the directories given with `--src` and `--out`, made absolute
+    pub fn dir_overrides(&self) -> DirOverrides {
+        let absolute = |path: &Option<PathBuf>| {
+            path.as_deref()
+                .map(|path| std::path::absolute(path).unwrap_or_else(|_| path.to_owned()))
+        };
+        DirOverrides {
+            src: absolute(&self.src),
+            build: absolute(&self.out),
+        }
+    }
 }
@@ -5,31 +5,39 @@ pub use args::*;
 use {
     crate::*,
     clap::Parser,
+    std::process::ExitCode,
     termimad::crossterm::style::Stylize,
 };
 
-/// Run the ddoc command line application
+/// Run the ddoc command line application, and return its exit status,
+/// which is a failure when an error was printed or the check or the
+/// build found errors
 ///
 /// # Errors
 /// Return errors only on unexpected failures, not on invalid
 /// data (those are printed to stderr)
-pub fn run() -> DdResult<()> {
+pub fn run() -> DdResult<ExitCode> {
     init_cli_log!();
     let args: Args = Args::parse();
     info!("args: {:#?}", &args);
 
     if args.help {
         args.print_help();
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     if args.version {
         println!("ddoc {}", env!("CARGO_PKG_VERSION"));
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.config_schema {
+        println!("{}", serde_json::to_string_pretty(&Config::json_schema())?);
+        return Ok(ExitCode::SUCCESS);
     }
 
     let Some(project_path) = args.project_path() else {
-        return Ok(()); // error already printed by args.project_path()
+        return Ok(ExitCode::FAILURE); // error already printed by args.project_path()
     };
 
     if args.init {
@@ -40,9 +48,9 @@ pub fn run() -> DdResult<()> {
                     "Cannot initialize ddoc project:".red().bold(),
                     reason,
                 );
-                Ok(())
+                Ok(ExitCode::FAILURE)
             }
-            res => res,
+            res => res.map(|_| ExitCode::SUCCESS),
         };
     }
 
@@ -53,21 +61,38 @@ pub fn run() -> DdResult<()> {
                 "Error:".red().bold(),
                 plugin_name.yellow(),
             );
-            return Ok(());
+            return Ok(ExitCode::FAILURE);
         };
         plugin.init(&project_path)?;
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.check {
+        let diagnostics = check_project(&project_path, args.dir_overrides());
+        for diagnostic in &diagnostics {
+            diagnostic.print(&project_path);
+        }
+        print_diagnostics_summary("Check", &diagnostics);
+        // as for a build, warnings make the check fail only with --strict
+        let failed = diagnostics.iter().any(|d| d.is_error() || args.strict);
+        if failed {
+            return Ok(ExitCode::FAILURE);
+        }
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let project_res = Project::load(&project_path);
+    let project_res = ProjectBuilder::new(&project_path)
+        .dirs(args.dir_overrides())
+        .minify(args.minify)
+        .load();
     let project_opt = project_res.as_ref().ok();
 
     if args.list_plugins {
         EmbeddedPlugin::print_list(project_opt);
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let project = match project_res {
+    let mut project = match project_res {
         Err(DdError::ConfigNotFound) => {
             // A frequent error is to run ddoc in a super director
             // of a ddoc project, so we check for that
@@ -77,47 +102,90 @@ pub fn run() -> DdResult<()> {
                     .map_or("that dir".to_string(), |s| s.to_string_lossy().to_string());
                 eprintln!(
                     "{}\nBut {} looks like a ddoc project\nSo maybe do {} then run ddoc there?",
-                    "No ddoc.hjson found in current directory".red().bold(),
+                    "No ddoc config file found in current directory"
+                        .red()
+                        .bold(),
                     subdir.to_string_lossy().yellow(),
                     format!("cd {}", name).green(),
                 );
             } else {
                 eprintln!(
                     "{}\nYou can initialize ddoc with {}",
-                    "No ddoc.hjson found".red().bold(),
+                    "No ddoc.hjson, ddoc.toml, or ddoc.json found".red().bold(),
                     "ddoc --init".green().bold(),
                 );
             }
-            return Ok(());
+            return Ok(ExitCode::FAILURE);
+        }
+        Err(DdError::MultipleConfigFiles { paths }) => {
+            eprintln!(
+                "{}\nKeep only one of {:?}",
+                "Several config files found".red().bold(),
+                paths,
+            );
+            return Ok(ExitCode::FAILURE);
         }
         res => res,
     }?;
 
     // Before everything else, we check the site doesn't require a newer ddoc version
-    if let Some(required_version) = &project.config.ddoc_version {
-        if version::is_current_version_older_than(required_version) {
-            eprintln!(
-                "{} This site requires ddoc version {} or newer (current version is {})",
-                "Error: ".red().bold(),
-                required_version.clone().yellow(),
-                DDOC_VERSION.red(),
-            );
-            return Ok(());
+    if let Some(required_version) = &project.config.ddoc_version
+        && version::is_current_version_older_than(required_version)
+    {
+        project.print_load_diagnostics();
+        eprintln!(
+            "{} This site requires ddoc version {} or newer (current version is {})",
+            "Error: ".red().bold(),
+            required_version.clone().yellow(),
+            DDOC_VERSION.red(),
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+
+    if let Some(ExportFormat::Epub) = args.export {
+        project.print_load_diagnostics();
+        for diagnostic in project.run_pre_build_hooks()? {
+            diagnostic.print(&project.root);
         }
+        let epub_path = project.epub_path();
+        project.write_epub(std::fs::File::create(&epub_path)?)?;
+        eprintln!("EPUB written in {}", epub_path.to_string_lossy().yellow());
+        return Ok(ExitCode::SUCCESS);
     }
 
-    // On launch, we clean the build directory to avoid stale files
-    // (and prevent users from thinking they should edit files there)
-    project.clean_build_dir()?;
-    project.build()?;
+    // On launch, without a manifest of a previous build by this version telling
+    // which files are stale, we remove the files an older version recorded
+    // (other files of the build directory are left untouched)
+    if !project.has_build_manifest() {
+        project.clean_build_dir()?;
+    }
+    let result = match project.build_with_hooks() {
+        Ok(result) => result,
+        Err(e) => {
+            project.print_load_diagnostics();
+            return Err(e);
+        }
+    };
+    result.print_diagnostics(&project.root);
     eprintln!(
         "Site built in {}",
         project.build_path.to_string_lossy().yellow()
     );
+    if !result.diagnostics.is_empty() {
+        print_diagnostics_summary("Build", &result.diagnostics);
+    }
+    if let Some(report_path) = &args.report {
+        result.write_report(report_path, &project.root)?;
+        eprintln!("Report written in {}", report_path.display());
+    }
+    let failed = result.has_errors() || (args.strict && !result.diagnostics.is_empty());
+    if failed && !args.serve {
+        return Ok(ExitCode::FAILURE);
+    }
 
     if args.serve {
         let port = args.port.unwrap_or(8004);
-        let server = Server::new(project.build_path.clone(), port)?;
+        let server = Server::new(project.build_path.clone(), port, project.config.base_path())?;
         eprintln!(
             "Serving {} at {}",
             project.config.title().yellow().bold(),
@@ -147,5 +215,5 @@ pub fn run() -> DdResult<()> {
         server.run()?;
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -64,10 +64,12 @@ impl NavComponents {
             ));
         }
         Element {
+            id: None,
             classes: vec![],
             content: ElementContent::DomTree {
                 tag: "body".to_string(),
                 children,
+                attributes: Attributes::default(),
             },
         }
     }
@@ -88,6 +90,7 @@ impl NavDir {
                         hamburger_checkbox: ui.hamburger_checkbox,
                     };
                     children.push(Element {
+                        id: None,
                         classes: vec![class.clone()],
                         content: ElementContent::Menu(menu_insert),
                     });
@@ -96,6 +99,7 @@ impl NavDir {
                     let mut nav_children = Vec::new();
                     for link in links {
                         nav_children.push(Element {
+                            id: None,
                             classes: link.classes(),
                             content: ElementContent::Link(link.to_nav_link()),
                         });
@@ -67,10 +67,8 @@ pub fn expand_special_var(
     var_name: &str,
     config: &Config,
 ) -> Option<String> {
-    if compat_allowed(config) {
-        if var_name == "search" {
-            return Some("javascript:ddoc_search.open();".to_string());
-        }
+    if compat_allowed(config) && var_name == "search" {
+        return Some("javascript:ddoc_search.open();".to_string());
     }
     None
 }
@@ -1,28 +1,47 @@
 use {
     indexmap::IndexMap,
+    schemars::{
+        JsonSchema,
+        Schema,
+    },
     serde::Deserialize,
+    serde_json::{
+        Map,
+        Value,
+        json,
+    },
 };
 
 pub type AttributeKey = String;
 
 /// The value of an attribute in a ddoc element (`ddoc-link`, `ddoc-menu`, etc).
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 #[serde(untagged)]
 pub enum AttributeValue {
     String(String),
     Bool(bool),
+    Number(f64),
 }
 impl AttributeValue {
     pub fn as_str(&self) -> Option<&str> {
         match self {
             Self::String(s) => Some(s),
-            Self::Bool(_) => None,
+            _ => None,
         }
     }
     pub fn as_bool(&self) -> Option<bool> {
         match self {
-            Self::String(_) => None,
             Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+    /// Return the value as a positive integer, if it's a number or a string
+    /// holding one
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Self::String(s) => s.parse().ok(),
+            Self::Bool(_) => None,
+            Self::Number(n) => (*n >= 0.0 && n.fract() == 0.0).then_some(*n as usize),
         }
     }
 }
@@ -35,8 +54,130 @@ impl std::fmt::Display for AttributeValue {
         match self {
             Self::String(s) => write!(f, "{}", s),
             Self::Bool(b) => write!(f, "{}", b),
+            Self::Number(n) => write!(f, "{}", n),
         }
     }
 }
 
 pub type Attributes = IndexMap<AttributeKey, AttributeValue>;
+
+/// The type of the value of an attribute of a `ddoc-` element
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeType {
+    String,
+    Boolean,
+    /// A strictly positive integer
+    Integer,
+    /// A text, which may also be written as a number or a boolean
+    Text,
+    /// One of the given strings
+    Enum(&'static [&'static str]),
+}
+
+/// The definition of an attribute of a `ddoc-` element, from which are
+/// made both the check of the configuration and the JSON schema
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttributeDef {
+    pub name: &'static str,
+    pub value_type: AttributeType,
+    /// Description, for the JSON schema, `None` for a deprecated alias
+    pub description: Option<&'static str>,
+    pub deprecated: bool,
+    pub required: bool,
+    /// Default value, as a JSON literal
+    pub default: Option<&'static str>,
+}
+
+impl AttributeDef {
+    pub const fn new(
+        name: &'static str,
+        value_type: AttributeType,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            value_type,
+            description: Some(description),
+            deprecated: false,
+            required: false,
+            default: None,
+        }
+    }
+    /// A deprecated, and undocumented, alias of another attribute
+    pub const fn alias(
+        name: &'static str,
+        value_type: AttributeType,
+    ) -> Self {
+        Self {
+            name,
+            value_type,
+            description: None,
+            deprecated: true,
+            required: false,
+            default: None,
+        }
+    }
+    pub const fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+    pub const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+    pub const fn with_default(
+        mut self,
+        json: &'static str,
+    ) -> Self {
+        self.default = Some(json);
+        self
+    }
+    fn schema(&self) -> Value {
+        let mut schema = match self.value_type {
+            AttributeType::String => json!({ "type": "string" }),
+            AttributeType::Boolean => json!({ "type": "boolean" }),
+            AttributeType::Integer => json!({ "type": "integer", "minimum": 1 }),
+            AttributeType::Text => json!({ "type": ["string", "number", "boolean"] }),
+            AttributeType::Enum(values) => json!({ "enum": values }),
+        };
+        if let Some(description) = self.description {
+            schema["description"] = description.into();
+        }
+        if self.deprecated {
+            schema["deprecated"] = true.into();
+        }
+        if let Some(default) = self
+            .default
+            .and_then(|json| serde_json::from_str(json).ok())
+        {
+            schema["default"] = default;
+        }
+        schema
+    }
+}
+
+/// Build the JSON schema of a `ddoc-` element, given the definitions of
+/// its attributes
+pub fn element_schema(
+    description: &str,
+    attributes: &[AttributeDef],
+) -> Schema {
+    let mut properties = Map::new();
+    for attribute in attributes {
+        properties.insert(attribute.name.to_string(), attribute.schema());
+    }
+    let required: Vec<&str> = attributes
+        .iter()
+        .filter(|attribute| attribute.required)
+        .map(|attribute| attribute.name)
+        .collect();
+    let mut schema = Map::new();
+    schema.insert("description".to_string(), description.into());
+    schema.insert("type".to_string(), "object".into());
+    schema.insert("properties".to_string(), properties.into());
+    if !required.is_empty() {
+        schema.insert("required".to_string(), required.into());
+    }
+    schema.insert("additionalProperties".to_string(), false.into());
+    schema.into()
+}
@@ -0,0 +1,116 @@
+use {
+    crate::*,
+    schemars::{
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+    },
+    std::borrow::Cow,
+};
+
+/// The settings for the insertion of the list of the pages of the current
+/// section, that is the pages listed with the current page in the site map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChildPages {
+    /// How many levels of sub menus are listed
+    pub depth: usize,
+    pub layout: ChildPagesLayout,
+    /// The invalid attribute values, as (value, error message), reported
+    /// when the element is written
+    pub invalid_values: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ChildPagesLayout {
+    /// A `<ul>` list, with nested lists for sub menus
+    #[default]
+    List,
+    /// Links which can be styled as cards, with nested `.page-cards`
+    /// containers for sub menus
+    Cards,
+}
+
+impl Default for ChildPages {
+    fn default() -> Self {
+        Self {
+            depth: 1,
+            layout: ChildPagesLayout::default(),
+            invalid_values: Vec::new(),
+        }
+    }
+}
+
+impl ChildPages {
+    /// The attributes of a `ddoc-page-list` element
+    pub const ATTRIBUTES: &[AttributeDef] = &[
+        AttributeDef::new(
+            "depth",
+            AttributeType::Integer,
+            "How many levels of sub menus are listed",
+        )
+        .with_default("1"),
+        AttributeDef::new(
+            "layout",
+            AttributeType::Enum(&["list", "cards"]),
+            "`list` for nested lists, `cards` for cards, with nested containers for sub menus",
+        )
+        .with_default(r#""list""#),
+    ];
+}
+
+impl From<Attributes> for ChildPages {
+    fn from(map: Attributes) -> Self {
+        let mut child_pages = ChildPages::default();
+        if let Some(depth) = map.get("depth") {
+            match depth.as_usize() {
+                Some(depth) if depth > 0 => child_pages.depth = depth,
+                _ => child_pages.invalid_values.push((
+                    depth.to_string(),
+                    format!("invalid ddoc-page-list depth: {depth} (must be at least 1)"),
+                )),
+            }
+        }
+        if let Some(layout) = map.get("layout") {
+            match layout.as_str() {
+                Some("list") => child_pages.layout = ChildPagesLayout::List,
+                Some("cards") => child_pages.layout = ChildPagesLayout::Cards,
+                _ => child_pages.invalid_values.push((
+                    layout.to_string(),
+                    format!("unknown ddoc-page-list layout: {layout} (expected list or cards)"),
+                )),
+            }
+        }
+        child_pages
+    }
+}
+
+impl JsonSchema for ChildPages {
+    fn schema_name() -> Cow<'static, str> {
+        "ChildPages".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        element_schema(
+            "A ddoc-page-list element, the list of the pages of the current section",
+            Self::ATTRIBUTES,
+        )
+    }
+}
+
+#[test]
+fn test_invalid_child_pages_attributes() {
+    let mut map = Attributes::new();
+    map.insert("depth".to_string(), AttributeValue::Number(0.0));
+    map.insert(
+        "layout".to_string(),
+        AttributeValue::String("grid".to_string()),
+    );
+    let child_pages = ChildPages::from(map);
+    assert_eq!(child_pages.depth, 1);
+    assert_eq!(child_pages.layout, ChildPagesLayout::List);
+    let values: Vec<_> = child_pages
+        .invalid_values
+        .iter()
+        .map(|(value, _)| value.as_str())
+        .collect();
+    assert_eq!(values, ["0", "grid"]);
+}
@@ -2,6 +2,7 @@ use crate::*;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Element {
+    pub id: Option<String>,
     pub classes: Vec<ClassName>,
     pub content: ElementContent,
 }
@@ -18,8 +19,11 @@ pub enum ElementContent {
     DomTree {
         tag: String,
         children: Vec<Element>,
+        attributes: Attributes,
     },
     Link(NavLink),
+    Include(Include),
+    ChildPages(ChildPages),
     Menu(Menu),
     Toc(Toc),
     Main,
@@ -46,13 +50,21 @@ impl Element {
             (
                 ElementContent::DomTree {
                     children: children1,
+                    attributes: attributes1,
                     ..
                 },
                 ElementContent::DomTree {
                     children: children2,
+                    attributes: attributes2,
                     ..
                 },
             ) => {
+                // on conflict, the attributes of self (eg the main config) win
+                for (key, value) in attributes2 {
+                    attributes1
+                        .entry(key.clone())
+                        .or_insert_with(|| value.clone());
+                }
                 let mut merged_elements = Vec::new();
                 for (i, e1) in children1.drain(..).enumerate() {
                     merged_elements.push(RankedSelectorElement {
@@ -63,11 +75,11 @@ impl Element {
                 }
                 for (i, e2) in children2.iter().enumerate() {
                     let selector = e2.selector();
-                    if let Some(e1) = merged_elements.iter_mut().find(|e| e.selector == selector) {
-                        if e1.merged_element.try_merge(e2) {
-                            e1.rank = i.max(e1.rank);
-                            continue;
-                        }
+                    if let Some(e1) = merged_elements.iter_mut().find(|e| e.selector == selector)
+                        && e1.merged_element.try_merge(e2)
+                    {
+                        e1.rank = i.max(e1.rank);
+                        continue;
                     }
                     merged_elements.push(RankedSelectorElement {
                         selector,
@@ -90,6 +102,8 @@ impl Element {
             ElementContent::DomLeaf { tag, .. } => tag,
             ElementContent::DomTree { tag, .. } => tag,
             ElementContent::Link(_) => "ddoc-link",
+            ElementContent::Include(_) => "ddoc-include",
+            ElementContent::ChildPages(_) => "ddoc-page-list",
             ElementContent::Menu(_) => "ddoc-menu",
             ElementContent::Toc(_) => "ddoc-toc",
             ElementContent::Main => "ddoc-main",
@@ -110,8 +124,13 @@ impl Element {
         let tag = tokens.next().unwrap_or("div").to_string();
         let classes = tokens.map(|s| s.to_string()).collect();
         Self {
+            id: None,
             classes,
-            content: ElementContent::DomTree { tag, children },
+            content: ElementContent::DomTree {
+                tag,
+                children,
+                attributes: Attributes::default(),
+            },
         }
     }
     pub fn visit<F>(
@@ -127,15 +146,28 @@ impl Element {
             }
         }
     }
+    /// Check whether some element of the tree includes the given file,
+    /// given by its path relative to the `src` directory
+    pub fn includes(
+        &self,
+        src: &str,
+    ) -> bool {
+        self.has(&mut |element: &Element| {
+            if let ElementContent::Include(include) = &element.content {
+                return include.is_file(src);
+            }
+            false
+        })
+    }
     pub fn has_href(
         &self,
         href: &str,
     ) -> bool {
         self.has(&mut |element: &Element| {
-            if let ElementContent::Link(link) = &element.content {
-                if link.href.as_deref() == Some(href) {
-                    return true;
-                }
+            if let ElementContent::Link(link) = &element.content
+                && link.href.as_deref() == Some(href)
+            {
+                return true;
             }
             false
         })
@@ -161,6 +193,10 @@ impl Element {
     }
     pub fn selector(&self) -> String {
         let mut selector = self.tag().to_string();
+        if let Some(id) = &self.id {
+            selector.push('#');
+            selector.push_str(id);
+        }
         for class in &self.classes {
             selector.push('.');
             selector.push_str(class.as_str());
@@ -180,15 +216,15 @@ impl Element {
     /// Print this element and its children in a human-readable way, for debugging purposes
     fn print(&self) {
         print!("{}", self.selector());
-        if let Some(children) = self.children() {
-            if !children.is_empty() {
-                print!("( ");
-                for child in children {
-                    child.print();
-                    print!(" ");
-                }
-                print!(")");
+        if let Some(children) = self.children()
+            && !children.is_empty()
+        {
+            print!("( ");
+            for child in children {
+                child.print();
+                print!(" ");
             }
+            print!(")");
         }
     }
 }
@@ -196,10 +232,12 @@ impl Element {
 impl Default for Element {
     fn default() -> Self {
         Self {
+            id: None,
             classes: vec![],
             content: ElementContent::DomTree {
                 tag: "div".to_string(),
                 children: vec![],
+                attributes: Attributes::default(),
             },
         }
     }
@@ -208,6 +246,7 @@ impl Default for Element {
 impl From<ElementContent> for Element {
     fn from(content: ElementContent) -> Self {
         Self {
+            id: None,
             classes: vec![],
             content,
         }
@@ -247,3 +286,23 @@ fn test_merge_order() {
     assert!(e21.try_merge(&e1));
     assert_eq!(e21, m);
 }
+
+#[test]
+fn test_merge_attributes() {
+    let with_attributes = |pairs: &[(&str, &str)]| {
+        let mut element = Element::new_composite("nav", vec![]);
+        if let ElementContent::DomTree { attributes, .. } = &mut element.content {
+            for (key, value) in pairs {
+                attributes.insert(key.to_string(), AttributeValue::String(value.to_string()));
+            }
+        }
+        element
+    };
+    let mut main = with_attributes(&[("role", "navigation")]);
+    let plugin = with_attributes(&[("role", "menu"), ("aria-label", "Main")]);
+    assert!(main.try_merge(&plugin));
+    assert_eq!(
+        main,
+        with_attributes(&[("role", "navigation"), ("aria-label", "Main")]),
+    );
+}
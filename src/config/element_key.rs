@@ -1,14 +1,18 @@
 use {
     crate::*,
+    lazy_regex::regex_is_match,
     std::{
         fmt,
         str::FromStr,
     },
 };
 
+/// The key of an element in the `body` configuration, in the
+/// form `tag#id.class1.class2` (the id and classes being optional)
 #[derive(Debug, Clone)]
 pub struct ElementKey {
     pub etype: ElementType,
+    pub id: Option<String>,
     pub classes: Vec<String>,
 }
 
@@ -17,6 +21,8 @@ pub enum ElementType {
     HtmlTag(String),
     Menu,
     Link,
+    Include,
+    ChildPages,
     Toc,
     Main,
     PageTitle,
@@ -31,6 +37,8 @@ impl fmt::Display for ElementType {
             ElementType::HtmlTag(tag) => write!(f, "{}", tag),
             ElementType::Menu => write!(f, "ddoc-menu"),
             ElementType::Link => write!(f, "ddoc-link"),
+            ElementType::Include => write!(f, "ddoc-include"),
+            ElementType::ChildPages => write!(f, "ddoc-page-list"),
             ElementType::Toc => write!(f, "ddoc-toc"),
             ElementType::Main => write!(f, "ddoc-main"),
             ElementType::PageTitle => write!(f, "ddoc-page-title"),
@@ -43,6 +51,9 @@ impl fmt::Display for ElementKey {
         f: &mut fmt::Formatter,
     ) -> fmt::Result {
         write!(f, "{}", &self.etype)?;
+        if let Some(id) = &self.id {
+            write!(f, "#{}", id)?;
+        }
         for class in &self.classes {
             write!(f, ".{}", class)?;
         }
@@ -54,18 +65,61 @@ impl FromStr for ElementKey {
     type Err = DdError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // FIXME check validity of tag/class names
-        let parts: Vec<&str> = s.split('.').collect();
-        let etype = match parts[0] {
+        let invalid = |reason: String| DdError::InvalidElementKey {
+            key: s.to_string(),
+            reason,
+        };
+        let tag_end = s.find(['.', '#']).unwrap_or(s.len());
+        let etype = match &s[..tag_end] {
+            "" => {
+                return Err(invalid("missing tag".to_string()));
+            }
             "ddoc-menu" => ElementType::Menu,
             "ddoc-link" => ElementType::Link,
+            "ddoc-include" => ElementType::Include,
+            "ddoc-page-list" => ElementType::ChildPages,
             "ddoc-toc" => ElementType::Toc,
             "ddoc-main" => ElementType::Main,
             "ddoc-page-title" => ElementType::PageTitle,
-            tag => ElementType::HtmlTag(tag.to_string()),
+            tag if tag.starts_with("ddoc-") => {
+                return Err(invalid(format!("unknown ddoc element {tag:?}")));
+            }
+            tag => {
+                if !regex_is_match!(r"^[a-zA-Z][a-zA-Z0-9]*(-[a-zA-Z0-9]+)*$", tag) {
+                    return Err(invalid(format!("invalid tag name {tag:?}")));
+                }
+                ElementType::HtmlTag(tag.to_string())
+            }
         };
-        let classes = parts[1..].iter().map(|s| s.to_string()).collect();
-        Ok(ElementKey { etype, classes })
+        let mut id = None;
+        let mut classes = Vec::new();
+        let mut rest = &s[tag_end..];
+        while let Some(sep) = rest.chars().next() {
+            let rest_end = rest[1..].find(['.', '#']).map_or(rest.len(), |i| i + 1);
+            let name = &rest[1..rest_end];
+            rest = &rest[rest_end..];
+            if sep == '#' {
+                if id.is_some() {
+                    return Err(invalid("more than one id".to_string()));
+                }
+                if name.is_empty() {
+                    return Err(invalid("empty id".to_string()));
+                }
+                if !regex_is_match!(r"^[_a-zA-Z][_a-zA-Z0-9-]*$", name) {
+                    return Err(invalid(format!("invalid id {name:?}")));
+                }
+                id = Some(name.to_string());
+            } else {
+                if name.is_empty() {
+                    return Err(invalid("empty class name".to_string()));
+                }
+                if !regex_is_match!(r"^-?[_a-zA-Z][_a-zA-Z0-9-]*$", name) {
+                    return Err(invalid(format!("invalid class name {name:?}")));
+                }
+                classes.push(name.to_string());
+            }
+        }
+        Ok(ElementKey { etype, id, classes })
     }
 }
 
@@ -75,3 +129,32 @@ impl<'de> serde::Deserialize<'de> for ElementKey {
         s.parse().map_err(serde::de::Error::custom)
     }
 }
+
+#[test]
+fn test_parse_element_key() {
+    let key: ElementKey = "nav#main-nav.wide.dark".parse().unwrap();
+    assert_eq!(key.etype, ElementType::HtmlTag("nav".to_string()));
+    assert_eq!(key.id.as_deref(), Some("main-nav"));
+    assert_eq!(key.classes, vec!["wide", "dark"]);
+    assert_eq!(key.to_string(), "nav#main-nav.wide.dark");
+    let key: ElementKey = "ddoc-link.home-link".parse().unwrap();
+    assert_eq!(key.etype, ElementType::Link);
+    assert_eq!(key.id, None);
+    let key: ElementKey = "div.wide#hero".parse().unwrap();
+    assert_eq!(key.id.as_deref(), Some("hero"));
+    assert_eq!(key.classes, vec!["wide"]);
+    for invalid in [
+        "",
+        ".wide",
+        "div..wide",
+        "div#",
+        "div#a#b",
+        "div#a b",
+        "div#1st",
+        "div.2col",
+        "my tag",
+        "ddoc-lnk",
+    ] {
+        assert!(invalid.parse::<ElementKey>().is_err(), "{invalid:?}");
+    }
+}
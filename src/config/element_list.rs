@@ -1,17 +1,28 @@
 use {
     crate::*,
+    schemars::{
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+        json_schema,
+    },
     serde::{
         Deserialize,
         de,
     },
-    std::fmt,
-    termimad::crossterm::style::Stylize,
+    std::{
+        borrow::Cow,
+        fmt,
+    },
 };
 
 /// A collection of elements, a vessel for deserializing
+///
+/// The scalar values of the map are attributes of the containing element.
 #[derive(Debug, Clone, Default)]
 pub struct ElementList {
     pub children: Vec<Element>,
+    pub attributes: Attributes,
 }
 
 pub struct ElementListDeserializer {}
@@ -34,64 +45,63 @@ impl<'de> de::Visitor<'de> for ElementListDeserializer {
         #[derive(Debug, Clone, Deserialize)]
         #[serde(untagged)]
         enum DeserContent {
+            Attribute(AttributeValue),
             Composite(ElementList),
-            Attributes(Attributes),
         }
         let mut children = Vec::new();
-        while let Some((key, value)) = access.next_entry::<ElementKey, DeserContent>()? {
-            let ElementKey { etype, classes: _ } = key;
-            let content = match (etype, value) {
-                (ElementType::HtmlTag(tag), DeserContent::Composite(comp)) => {
-                    ElementContent::DomTree {
-                        tag,
-                        children: comp.children,
-                    }
+        let mut attributes = Attributes::default();
+        while let Some(key) = access.next_key::<String>()? {
+            let comp = match access.next_value::<DeserContent>()? {
+                DeserContent::Attribute(value) => {
+                    attributes.insert(key, value);
+                    continue;
                 }
-                (ElementType::HtmlTag(tag), DeserContent::Attributes(mut attrs)) => {
-                    let text = attrs.shift_remove("text").map(Text::from);
-                    let raw_html = attrs.shift_remove("html").map(|v| v.to_string());
-                    ElementContent::DomLeaf {
-                        tag,
-                        text,
-                        raw_html,
-                        attributes: attrs,
+                DeserContent::Composite(comp) => comp,
+            };
+            let ElementKey { etype, id, classes } = key.parse().map_err(de::Error::custom)?;
+            let content = match etype {
+                ElementType::HtmlTag(tag) => {
+                    if comp.children.is_empty() && !comp.attributes.is_empty() {
+                        let mut attrs = comp.attributes;
+                        let text = attrs.shift_remove("text").map(Text::from);
+                        let raw_html = attrs.shift_remove("html").map(|v| v.to_string());
+                        ElementContent::DomLeaf {
+                            tag,
+                            text,
+                            raw_html,
+                            attributes: attrs,
+                        }
+                    } else {
+                        ElementContent::DomTree {
+                            tag,
+                            children: comp.children,
+                            attributes: comp.attributes,
+                        }
                     }
                 }
-                (ElementType::Link, DeserContent::Attributes(attrs)) => {
-                    let nav_link: NavLink = attrs.into();
-                    ElementContent::Link(nav_link)
-                }
-                (ElementType::Menu, DeserContent::Attributes(attrs)) => {
-                    let menu_insert: Menu = attrs.into();
-                    ElementContent::Menu(menu_insert)
-                }
-                (ElementType::Toc, DeserContent::Attributes(attrs)) => {
-                    let toc: Toc = attrs.into();
-                    ElementContent::Toc(toc)
-                }
-                (ElementType::Menu, _) => ElementContent::Menu(Menu::default()),
-                (ElementType::Toc, _) => ElementContent::Toc(Toc::default()),
-                (ElementType::Main, _) => ElementContent::Main,
-                (ElementType::PageTitle, _) => ElementContent::PageTitle,
-                (etype, value) => {
-                    eprintln!(
-                        "{}: invalid element type {} for value {:?}",
-                        "error".red(),
-                        etype.to_string().yellow(),
-                        value,
-                    );
+                etype if !comp.children.is_empty() => {
                     return Err(de::Error::custom(format!(
-                        "invalid element type {:?} for value {:?}",
-                        etype, value
+                        "element {etype} can't have sub elements"
                     )));
                 }
+                ElementType::Link => ElementContent::Link(comp.attributes.into()),
+                ElementType::Include => ElementContent::Include(comp.attributes.into()),
+                ElementType::ChildPages => ElementContent::ChildPages(comp.attributes.into()),
+                ElementType::Menu => ElementContent::Menu(comp.attributes.into()),
+                ElementType::Toc => ElementContent::Toc(comp.attributes.into()),
+                ElementType::Main => ElementContent::Main,
+                ElementType::PageTitle => ElementContent::PageTitle,
             };
             children.push(Element {
-                classes: key.classes,
+                id,
+                classes,
                 content,
             });
         }
-        Ok(Self::Value { children })
+        Ok(Self::Value {
+            children,
+            attributes,
+        })
     }
 }
 impl<'de> de::Deserialize<'de> for ElementList {
@@ -102,3 +112,60 @@ impl<'de> de::Deserialize<'de> for ElementList {
         deserializer.deserialize_map(ElementListDeserializer {})
     }
 }
+impl JsonSchema for ElementList {
+    fn schema_name() -> Cow<'static, str> {
+        "ElementList".into()
+    }
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        // keys are element keys (eg `nav.before-menu` or `ddoc-link#home`),
+        // except scalar values which are attributes of the containing element
+        let key_pattern = |etype: ElementType| format!(r"^{etype}([#.]|$)");
+        json_schema!({
+            "description": "Elements, keyed by their tag, id, and classes, eg `nav#top.menu`, and attributes",
+            "type": "object",
+            "patternProperties": {
+                key_pattern(ElementType::Link): generator.subschema_for::<NavLink>(),
+                key_pattern(ElementType::Menu): generator.subschema_for::<Menu>(),
+                key_pattern(ElementType::Toc): generator.subschema_for::<Toc>(),
+                key_pattern(ElementType::Include): generator.subschema_for::<Include>(),
+                key_pattern(ElementType::ChildPages): generator.subschema_for::<ChildPages>(),
+                key_pattern(ElementType::Main): element_schema(
+                    "A ddoc-main element, the HTML made from the Markdown of the page",
+                    &[],
+                ),
+                key_pattern(ElementType::PageTitle): element_schema(
+                    "A ddoc-page-title element, the title of the page",
+                    &[],
+                ),
+                "^(?!ddoc-)[a-zA-Z]": {
+                    "anyOf": [
+                        generator.subschema_for::<AttributeValue>(),
+                        generator.subschema_for::<ElementList>(),
+                    ],
+                },
+            },
+            "additionalProperties": false,
+        })
+    }
+}
+
+#[test]
+fn test_element_schemas_match_attributes() {
+    fn check<T: JsonSchema>(attributes: &[AttributeDef]) {
+        let schema = T::json_schema(&mut SchemaGenerator::default());
+        let properties = schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .unwrap();
+        let mut keys: Vec<&str> = properties.keys().map(String::as_str).collect();
+        let mut attributes: Vec<&str> = attributes.iter().map(|a| a.name).collect();
+        keys.sort_unstable();
+        attributes.sort_unstable();
+        assert_eq!(keys, attributes, "schema of {}", T::schema_name());
+    }
+    check::<NavLink>(NavLink::ATTRIBUTES);
+    check::<Menu>(Menu::ATTRIBUTES);
+    check::<Toc>(Toc::ATTRIBUTES);
+    check::<Include>(Include::ATTRIBUTES);
+    check::<ChildPages>(ChildPages::ATTRIBUTES);
+}
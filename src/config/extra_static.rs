@@ -0,0 +1,142 @@
+use {
+    crate::*,
+    globset::{
+        Glob,
+        GlobSet,
+        GlobSetBuilder,
+    },
+    schemars::JsonSchema,
+    serde::Deserialize,
+    std::path::{
+        Path,
+        PathBuf,
+    },
+};
+
+/// A file or directory of the `src` directory, copied verbatim into the site
+///
+/// When it's a directory, its files may be filtered with globs, matched
+/// against their paths relative to this directory.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(from = "ExtraStaticDef")]
+pub struct ExtraStatic {
+    /// Path relative to the `src` directory, eg `fonts` or `CNAME`
+    pub path: String,
+    /// If not empty, only the files matching one of those globs are copied
+    pub include: Vec<String>,
+    /// Files matching one of those globs aren't copied
+    pub exclude: Vec<String>,
+}
+
+/// The accepted forms of an extra static: either just a path, or
+/// a path with include and exclude globs
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum ExtraStaticDef {
+    /// Path of the file or directory, relative to the `src` directory
+    Path(String),
+    Filtered {
+        /// Path of the file or directory, relative to the `src` directory
+        path: String,
+        /// If not empty, only the files matching one of those globs are copied
+        #[serde(default)]
+        include: Vec<String>,
+        /// Files matching one of those globs aren't copied
+        #[serde(default)]
+        exclude: Vec<String>,
+    },
+}
+
+impl From<ExtraStaticDef> for ExtraStatic {
+    fn from(def: ExtraStaticDef) -> Self {
+        match def {
+            ExtraStaticDef::Path(path) => Self {
+                path,
+                ..Default::default()
+            },
+            ExtraStaticDef::Filtered {
+                path,
+                include,
+                exclude,
+            } => Self {
+                path,
+                include,
+                exclude,
+            },
+        }
+    }
+}
+
+impl ExtraStatic {
+    /// The path, without leading or trailing slash
+    pub fn rel_path(&self) -> &str {
+        self.path.trim_matches('/')
+    }
+    /// List the files to copy, with their path relative to the
+    /// site root, and their path on disk
+    ///
+    /// # Errors
+    /// Return `DdError::InvalidGlob` if a glob can't be parsed
+    pub fn list_files(
+        &self,
+        src_path: &Path,
+        files: &mut Vec<(String, PathBuf)>,
+    ) -> DdResult<()> {
+        let rel_path = self.rel_path();
+        let path = src_path.join(rel_path);
+        if path.is_file() {
+            files.push((rel_path.to_string(), path));
+            return Ok(());
+        }
+        if !path.is_dir() {
+            return Ok(());
+        }
+        let include = build_glob_set(&self.include)?;
+        let exclude = build_glob_set(&self.exclude)?;
+        let mut dir_files = Vec::new();
+        list_normal_files_recursive(&path, "", &mut dir_files)?;
+        for (file_rel_path, file_path) in dir_files {
+            if !self.include.is_empty() && !include.is_match(&file_rel_path) {
+                continue;
+            }
+            if exclude.is_match(&file_rel_path) {
+                continue;
+            }
+            files.push((format!("{rel_path}/{file_rel_path}"), file_path));
+        }
+        Ok(())
+    }
+}
+
+fn build_glob_set(globs: &[String]) -> DdResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = Glob::new(glob).map_err(|e| DdError::InvalidGlob {
+            glob: glob.clone(),
+            reason: e.kind().to_string(),
+        })?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| DdError::InvalidGlob {
+        glob: globs.join(", "),
+        reason: e.kind().to_string(),
+    })
+}
+
+#[test]
+fn test_extra_static_forms() {
+    let statics: Vec<ExtraStatic> = deser_hjson::from_str(
+        r#"[
+            CNAME
+            {
+                path: fonts
+                include: ["*.woff2"]
+            }
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(statics[0].path, "CNAME");
+    assert!(statics[0].include.is_empty());
+    assert_eq!(statics[1].path, "fonts");
+    assert_eq!(statics[1].include, vec!["*.woff2".to_string()]);
+}
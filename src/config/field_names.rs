@@ -0,0 +1,63 @@
+use serde::{
+    Deserialize,
+    de::{
+        self,
+        Visitor,
+        value::Error,
+    },
+};
+
+/// Return the names (and aliases) of the fields of a struct, as declared to
+/// serde when deserializing it.
+///
+/// This is empty for structs with a flattened field, as serde doesn't
+/// declare their fields.
+pub fn serde_field_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut names: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNamesCollector { names: &mut names });
+    names
+}
+
+/// A deserializer which doesn't deserialize anything but collects the names
+/// of the fields of the struct it's asked for
+struct FieldNamesCollector<'n> {
+    names: &'n mut &'static [&'static str],
+}
+
+impl<'de> de::Deserializer<'de> for FieldNamesCollector<'_> {
+    type Error = Error;
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        *self.names = fields;
+        Err(de::Error::custom("field names collected"))
+    }
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[test]
+fn test_serde_field_names() {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    #[allow(dead_code)]
+    struct S {
+        some_field: u8,
+        #[serde(alias = "b")]
+        a: u8,
+    }
+    assert_eq!(serde_field_names::<S>(), ["some-field", "a", "b"]);
+    assert!(serde_field_names::<u8>().is_empty());
+}
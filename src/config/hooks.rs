@@ -0,0 +1,53 @@
+use {
+    schemars::JsonSchema,
+    serde::Deserialize,
+    std::fmt,
+};
+
+/// Shell commands run before and after each build, with the project
+/// root as current directory
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct Hooks {
+    /// Commands run before building the site, eg to generate Markdown files
+    #[serde(default)]
+    pub pre_build: Vec<String>,
+    /// Commands run after building the site, eg to post-process its files,
+    /// unless a pre-build command failed
+    #[serde(default)]
+    pub post_build: Vec<String>,
+}
+
+/// When hooks are run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreBuild,
+    PostBuild,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_build.is_empty() && self.post_build.is_empty()
+    }
+    pub fn commands(
+        &self,
+        stage: HookStage,
+    ) -> &[String] {
+        match stage {
+            HookStage::PreBuild => &self.pre_build,
+            HookStage::PostBuild => &self.post_build,
+        }
+    }
+}
+
+impl fmt::Display for HookStage {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Self::PreBuild => write!(f, "pre-build"),
+            Self::PostBuild => write!(f, "post-build"),
+        }
+    }
+}
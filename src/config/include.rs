@@ -0,0 +1,99 @@
+use {
+    crate::*,
+    schemars::{
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+    },
+    std::{
+        borrow::Cow,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// The settings for the inclusion of a file of the `src` directory in a web page.
+///
+/// Markdown files are rendered like pages, with links and images rewritten
+/// relative to the hosting page. HTML files are inlined, with their `{{--var}}`
+/// expansions replaced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Include {
+    /// Path to the included file, relative to the `src` directory
+    pub src: String,
+}
+
+impl Include {
+    /// The attributes of a `ddoc-include` element
+    pub const ATTRIBUTES: &[AttributeDef] = &[AttributeDef::new(
+        "src",
+        AttributeType::String,
+        "Path to the included file, relative to the src directory",
+    )
+    .required()];
+
+    /// Return the path of the included file, unless it's outside the
+    /// `src` directory
+    pub fn path(
+        &self,
+        src_path: &Path,
+    ) -> Option<PathBuf> {
+        let path = normalize_path(&src_path.join(&self.src));
+        path.starts_with(normalize_path(src_path)).then_some(path)
+    }
+    /// Tell whether the included file is the given one, given by its path
+    /// relative to the `src` directory (eg `./footer.md` is `footer.md`)
+    pub fn is_file(
+        &self,
+        rel_path: &str,
+    ) -> bool {
+        normalize_path(Path::new(&self.src)) == normalize_path(Path::new(rel_path))
+    }
+    pub fn is_markdown(&self) -> bool {
+        self.src.ends_with(".md")
+    }
+    pub fn is_html(&self) -> bool {
+        self.src.ends_with(".html") || self.src.ends_with(".htm")
+    }
+}
+
+impl From<Attributes> for Include {
+    fn from(map: Attributes) -> Self {
+        let mut include = Include::default();
+        if let Some(s) = map.get("src").and_then(AttributeValue::as_str) {
+            include.src = s.to_string();
+        }
+        include
+    }
+}
+
+impl JsonSchema for Include {
+    fn schema_name() -> Cow<'static, str> {
+        "Include".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        element_schema(
+            "A ddoc-include element, the content of a markdown or HTML file",
+            Self::ATTRIBUTES,
+        )
+    }
+}
+
+#[test]
+fn test_include_path() {
+    let include = |src: &str| Include {
+        src: src.to_string(),
+    };
+    let src_path = Path::new("/doc/src");
+    assert_eq!(
+        include("parts/../intro.md").path(src_path),
+        Some(PathBuf::from("/doc/src/intro.md")),
+    );
+    assert_eq!(include("../../etc/passwd").path(src_path), None);
+    assert_eq!(include("/etc/passwd").path(src_path), None);
+    assert!(include("./footer.md").is_file("footer.md"));
+    assert!(include("parts/../footer.md").is_file("footer.md"));
+    assert!(!include("parts/footer.md").is_file("footer.md"));
+}
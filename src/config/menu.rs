@@ -1,6 +1,12 @@
 use {
     crate::*,
+    schemars::{
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+    },
     std::{
+        borrow::Cow,
         fmt,
         str::FromStr,
     },
@@ -12,6 +18,19 @@ pub struct Menu {
     pub hamburger_checkbox: bool,
 }
 
+impl Menu {
+    /// The attributes of a `ddoc-menu` element
+    pub const ATTRIBUTES: &[AttributeDef] = &[
+        AttributeDef::new(
+            "hamburger-checkbox",
+            AttributeType::Boolean,
+            "Whether to include a checkbox which can be styled into a hamburger menu",
+        )
+        .with_default("true"),
+        AttributeDef::alias("hamburger_checkbox", AttributeType::Boolean),
+    ];
+}
+
 impl FromStr for Menu {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -38,10 +57,9 @@ impl From<Attributes> for Menu {
         if let Some(v) = map
             .get("hamburger_checkbox")
             .or_else(|| map.get("hamburger-checkbox"))
+            && let Some(b) = v.as_bool()
         {
-            if let Some(b) = v.as_bool() {
-                menu_insert.hamburger_checkbox = b;
-            }
+            menu_insert.hamburger_checkbox = b;
         }
         menu_insert
     }
@@ -69,3 +87,14 @@ impl<'de> serde::Deserialize<'de> for Menu {
         s.parse().map_err(serde::de::Error::custom)
     }
 }
+impl JsonSchema for Menu {
+    fn schema_name() -> Cow<'static, str> {
+        "Menu".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        element_schema(
+            "A ddoc-menu element, the site map as a menu",
+            Self::ATTRIBUTES,
+        )
+    }
+}
@@ -1,7 +1,12 @@
 mod attribute;
+mod child_pages;
 mod element;
 mod element_key;
 mod element_list;
+mod extra_static;
+mod field_names;
+mod hooks;
+mod include;
 mod menu;
 mod nav_link;
 mod page_list;
@@ -10,9 +15,14 @@ mod toc;
 
 pub use {
     attribute::*,
+    child_pages::*,
     element::*,
     element_key::*,
     element_list::*,
+    extra_static::*,
+    field_names::*,
+    hooks::*,
+    include::*,
     menu::*,
     nav_link::*,
     page_list::*,
@@ -26,54 +36,183 @@ use {
         *,
     },
     indexmap::IndexMap,
+    lazy_regex::{
+        regex_captures,
+        regex_replace_all,
+    },
+    schemars::{
+        JsonSchema,
+        Schema,
+    },
     serde::{
         Deserialize,
         Deserializer,
     },
-    std::path::Path,
+    std::path::{
+        Path,
+        PathBuf,
+    },
 };
 
+/// Name of the config file written by `ddoc --init`
 pub static CONFIG_FILE_NAME: &str = "ddoc.hjson";
 
+/// Directory of the pages and static files, relative to the project
+/// root, when not set in the config
+pub static DEFAULT_SRC_DIR: &str = "src";
+
+/// Directory where the site is built, relative to the project root,
+/// when not set in the config
+pub static DEFAULT_BUILD_DIR: &str = "site";
+
+/// Accepted names for the config file of a project or plugin,
+/// only one of them may be present
+pub static CONFIG_FILE_NAMES: &[&str] = &["ddoc.hjson", "ddoc.toml", "ddoc.json"];
+
 pub type ClassName = String;
 
-#[derive(Debug, Default, Clone, Deserialize)]
+/// The configuration of a ddoc site or plugin, read from its ddoc.hjson file
+#[derive(Debug, Default, Clone, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct Config {
+    /// Title of the site, available as `--title`
     pub title: Option<String>,
+    /// Description of the site, available as `--description`
     pub description: Option<String>,
+    /// Language of the site, eg `fr` or `en-US`, declared in the pages
+    /// and in the EPUB export, `en` by default
+    pub language: Option<String>,
+    /// Format of the `<title>` of pages, with `{page}` and `{site}`
+    /// placeholders, default being `{page} - {site}`
+    pub title_format: Option<String>,
+    /// Public URL of the site, eg `https://dystroy.org/ddoc/`, needed
+    /// for the canonical links and the `og:url` and `og:image` tags
+    pub base_url: Option<String>,
+    /// Path under which the site is deployed, eg `/docs/`, by default
+    /// the path of the `base-url`
+    pub base_path: Option<String>,
+    /// Default image for social previews of pages (Open Graph, Twitter cards)
+    pub image: Option<String>,
+    /// Whether to copy the JS, CSS and image files to content-hashed names,
+    /// like `main.3f2a9c1b.css`, for cache busting
+    #[serde(default)]
+    pub fingerprint_assets: bool,
+    /// Whether to concatenate the CSS files, and the JS files, into single
+    /// files linked from the pages
+    #[serde(default)]
+    pub bundle_assets: bool,
+    /// Whether to also build `all/index.html`, with all pages in one,
+    /// for printing or searching the whole site
+    #[serde(default)]
+    pub print_page: bool,
+    /// Whether to also publish the Markdown of each page, as
+    /// `page/index.md`, and the `llms.txt` and `llms-full.txt` files,
+    /// for tools and language models
+    #[serde(default)]
+    pub llms_txt: bool,
+    /// Directory of the pages and static files, relative to the
+    /// project root, `src` by default
+    pub src_dir: Option<String>,
+    /// Directory where the site is built, relative to the project
+    /// root, `site` by default
+    pub build_dir: Option<String>,
+    /// Other files and directories of the `src` directory to copy
+    /// verbatim into the site, eg `fonts` or `CNAME`
+    #[serde(default)]
+    pub extra_statics: Vec<ExtraStatic>,
+    /// Commands to run before and after each build
+    #[serde(default)]
+    pub hooks: Hooks,
+    /// Minimum version of ddoc required to build the site
     pub ddoc_version: Option<String>,
+    /// Plugins of the `plugins` directory to apply to the site
     #[serde(default, alias = "active-plugins")]
     pub active_plugins: Vec<String>,
+    /// All pages of the site, with their titles, as a tree of menus
     #[serde(default, alias = "pages", alias = "menu")]
+    #[schemars(extend("x-aliases" = ["pages", "menu"]))]
     pub site_map: PageList,
+    /// Path to the favicon, eg `img/favicon.ico`
     pub favicon: Option<String>,
     /// for compatibility with ddoc (0.11-), this is loaded but only used
     /// through conversion to the new `body` field
     #[serde(flatten)]
+    #[schemars(skip)]
     pub old: NavComponents,
+    /// Content of the `<body>` element of every page
     #[serde(
         default = "default_body_element",
         deserialize_with = "deserialize_body_element"
     )]
+    #[schemars(with = "ElementList")]
     pub body: Element,
+    /// Variables which can be used in elements with `--name`
     #[serde(default)]
     pub vars: IndexMap<String, String>,
 }
 
 impl Config {
-    /// Read the ddoc.hjson configuration file at the root of a ddoc module
+    /// Return the properties which may be found at the root of a config
+    /// file: the ones of the schema, their aliases, and the ones of the
+    /// deprecated `NavComponents`
+    pub fn keys() -> Vec<String> {
+        let schema = Self::json_schema();
+        let mut keys = Vec::new();
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (key, property) in properties {
+                keys.push(key.clone());
+                if let Some(aliases) = property.get("x-aliases").and_then(|a| a.as_array()) {
+                    keys.extend(aliases.iter().filter_map(|a| a.as_str()).map(String::from));
+                }
+            }
+        }
+        keys.extend(
+            serde_field_names::<NavComponents>()
+                .iter()
+                .map(|key| key.to_string()),
+        );
+        keys
+    }
+
+    /// Build the JSON Schema describing the configuration file
+    pub fn json_schema() -> Schema {
+        schemars::schema_for!(Config)
+    }
+
+    /// Tell whether the file at `path` is named like a config file
+    pub fn is_config_file(path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| CONFIG_FILE_NAMES.contains(&name))
+    }
+    /// Return the path to the config file (ddoc.hjson, ddoc.toml, or ddoc.json)
+    /// at the root of a ddoc module, if there's one
+    ///
+    /// # Errors
+    /// Return `DdError::MultipleConfigFiles` if several config files are found
+    pub fn path_in_dir(path: &Path) -> DdResult<Option<PathBuf>> {
+        let mut paths: Vec<PathBuf> = CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| path.join(name))
+            .filter(|path| path.exists())
+            .collect();
+        if paths.len() > 1 {
+            return Err(DdError::MultipleConfigFiles { paths });
+        }
+        Ok(paths.pop())
+    }
+    /// Read the configuration file at the root of a ddoc module
     ///
-    /// Return both the config and the path where it was found
+    /// Return both the config and the path where it was found, or
+    /// None if there's no config file
     ///
     /// # Errors
-    /// Return `DdError::ConfigNotFound` if no ddoc.hjson is found at the specified path
+    /// Return `DdError::MultipleConfigFiles` if there are several config files
     /// or other `DdError` variants on read/parse errors
     pub fn in_dir(path: &Path) -> DdResult<Option<Sourced<Self>>> {
-        let config_path = path.join(CONFIG_FILE_NAME);
-        if !config_path.exists() {
+        let Some(config_path) = Self::path_in_dir(path)? else {
             return Ok(None);
-        }
+        };
         let config: Config = read_file(&config_path)?;
         let config = Sourced::new(config, config_path);
         Ok(Some(config))
@@ -87,6 +226,66 @@ impl Config {
     pub fn favicon(&self) -> Option<&str> {
         self.favicon.as_deref().filter(|s| !s.is_empty())
     }
+    pub fn image(&self) -> Option<&str> {
+        self.image.as_deref().filter(|s| !s.is_empty())
+    }
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref().filter(|s| !s.is_empty())
+    }
+    /// Return the path under which the site is deployed, starting
+    /// and ending with a `/`
+    pub fn base_path(&self) -> String {
+        let path = self
+            .base_path
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .or_else(|| {
+                let url = self.base_url()?;
+                regex_captures!(r"^[a-zA-Z][\w+.-]*://[^/]+(/.*)?$", url).map(|(_, path)| path)
+            })
+            .unwrap_or_default()
+            .trim_matches('/');
+        if path.is_empty() {
+            "/".to_string()
+        } else {
+            format!("/{path}/")
+        }
+    }
+    /// The language of the site, `en` when not set
+    pub fn language(&self) -> &str {
+        self.language.as_deref().unwrap_or("en")
+    }
+    /// Build the title of a page, according to the `title-format`
+    pub fn page_title(
+        &self,
+        page_title: &str,
+    ) -> String {
+        let format = self.title_format.as_deref().unwrap_or("{page} - {site}");
+        regex_replace_all!(r"\{(page|site)\}", format, |_, name: &str| {
+            if name == "page" {
+                page_title
+            } else {
+                self.title()
+            }
+        })
+        .into_owned()
+    }
+    /// Return the absolute URL of a file of the site, given its path relative
+    /// to the site's root (eg `img/card.png`), if a base URL is configured
+    pub fn absolute_url(
+        &self,
+        path: &str,
+    ) -> Option<String> {
+        if path.starts_with("https://") || path.starts_with("http://") {
+            return Some(path.to_string());
+        }
+        let base_url = self.base_url()?;
+        Some(format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            path.trim_start_matches('/'),
+        ))
+    }
     pub fn var(
         &self,
         name: &str,
@@ -117,6 +316,18 @@ impl Config {
         if self.favicon.is_none() {
             self.favicon = other.favicon.clone();
         }
+        if self.title_format.is_none() {
+            self.title_format = other.title_format.clone();
+        }
+        if self.base_url.is_none() {
+            self.base_url = other.base_url.clone();
+        }
+        if self.base_path.is_none() {
+            self.base_path = other.base_path.clone();
+        }
+        if self.image.is_none() {
+            self.image = other.image.clone();
+        }
         for (key, value) in &other.vars {
             if !self.vars.contains_key(key) {
                 self.vars.insert(key.clone(), value.clone());
@@ -137,10 +348,12 @@ impl Config {
 
 fn default_body_element() -> Element {
     Element {
+        id: None,
         classes: vec![],
         content: ElementContent::DomTree {
             tag: "body".to_string(),
             children: vec![],
+            attributes: Attributes::default(),
         },
     }
 }
@@ -149,11 +362,59 @@ fn deserialize_body_element<'de, D: Deserializer<'de>>(
 ) -> Result<Element, D::Error> {
     let element_list = ElementList::deserialize(deserializer)?;
     let element = Element {
+        id: None,
         classes: vec![],
         content: ElementContent::DomTree {
             tag: "body".to_string(),
             children: element_list.children,
+            attributes: element_list.attributes,
         },
     };
     Ok(element)
 }
+
+#[test]
+fn test_page_title_and_absolute_url() {
+    let mut config = Config {
+        title: Some("Site".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(config.page_title("Page"), "Page - Site");
+    assert_eq!(config.absolute_url("img/a.png"), None);
+    config.title_format = Some("{site}: {page}".to_string());
+    config.base_url = Some("https://example.com/doc/".to_string());
+    assert_eq!(config.page_title("Page"), "Site: Page");
+    // a page title containing a placeholder isn't expanded
+    assert_eq!(config.page_title("About {site}"), "Site: About {site}");
+    assert_eq!(
+        config.absolute_url("/img/a.png").as_deref(),
+        Some("https://example.com/doc/img/a.png"),
+    );
+    assert_eq!(
+        config.absolute_url("https://cdn.com/a.png").as_deref(),
+        Some("https://cdn.com/a.png"),
+    );
+}
+
+#[test]
+fn test_base_path() {
+    let mut config = Config::default();
+    assert_eq!(config.base_path(), "/");
+    config.base_url = Some("https://example.com".to_string());
+    assert_eq!(config.base_path(), "/");
+    config.base_url = Some("https://example.com/docs".to_string());
+    assert_eq!(config.base_path(), "/docs/");
+    config.base_path = Some("site/v2/".to_string());
+    assert_eq!(config.base_path(), "/site/v2/");
+}
+
+#[test]
+fn test_config_keys() {
+    let keys = Config::keys();
+    for key in [
+        "title", "language", "hooks", "site-map", "pages", "menu", "body", "vars", "header", "ui",
+    ] {
+        assert!(keys.iter().any(|k| k == key), "missing key {key}");
+    }
+    assert!(!keys.iter().any(|k| k == "old"));
+}
@@ -1,7 +1,12 @@
 use {
     crate::*,
     indexmap::IndexMap,
-    termimad::crossterm::style::Stylize,
+    schemars::{
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+    },
+    std::borrow::Cow,
 };
 
 /// A single link in the navigation bar
@@ -19,6 +24,40 @@ pub enum NavLinkPart {
     InlineImg { src: String },
 }
 
+impl NavLink {
+    /// The attributes of a `ddoc-link` element
+    pub const ATTRIBUTES: &[AttributeDef] = &[
+        AttributeDef::new(
+            "href",
+            AttributeType::String,
+            "Target of the link: an URL, a path to a page (eg `/guide/help.md`), or an expansion (eg `--previous`)",
+        ),
+        AttributeDef::new(
+            "img",
+            AttributeType::String,
+            "Path to an image, eg `img/logo.svg`",
+        ),
+        AttributeDef::new(
+            "inline",
+            AttributeType::String,
+            "Path to an SVG image to inline in the link",
+        ),
+        AttributeDef::new(
+            "alt",
+            AttributeType::String,
+            "Alternative text of the image",
+        ),
+        AttributeDef::new("label", AttributeType::Text, "Text of the link"),
+        AttributeDef::new(
+            "target",
+            AttributeType::String,
+            "Target of the link, eg `_blank`",
+        ),
+        AttributeDef::alias("url", AttributeType::String),
+        AttributeDef::alias("link_target", AttributeType::String),
+    ];
+}
+
 impl From<Attributes> for NavLink {
     fn from(map: IndexMap<AttributeKey, AttributeValue>) -> Self {
         let mut alt = None;
@@ -69,11 +108,8 @@ impl From<Attributes> for NavLink {
                     target = Some(value.to_string());
                 }
                 key => {
-                    eprintln!(
-                        "{}: unknown attribute in nav-link: {}",
-                        "warning".yellow().bold(),
-                        key.red(),
-                    );
+                    // reported with its location when loading the project
+                    warn!("unknown attribute in nav-link: {key}");
                 }
             }
         }
@@ -84,3 +120,15 @@ impl From<Attributes> for NavLink {
         }
     }
 }
+
+impl JsonSchema for NavLink {
+    fn schema_name() -> Cow<'static, str> {
+        "NavLink".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        element_schema(
+            "A ddoc-link element, a link made of images and labels",
+            Self::ATTRIBUTES,
+        )
+    }
+}
@@ -1,6 +1,7 @@
 use {
     crate::*,
     indexmap::IndexMap,
+    schemars::JsonSchema,
     serde::{
         Deserialize,
         Serialize,
@@ -8,14 +9,16 @@ use {
     std::fmt::Write,
 };
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// A page, or a sub menu
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(untagged)]
 pub enum ListItem {
     Page(PagePath),
     List(PageList),
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+/// A list of pages and sub menus, keyed by their titles
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(transparent)]
 pub struct PageList {
     pub items: IndexMap<String, ListItem>,
@@ -37,6 +40,20 @@ impl PageList {
         }
         None
     }
+    /// Return the sub menu at the end of the given path of titles
+    /// (the list itself if the path is empty)
+    pub fn sub_list(
+        &self,
+        titles: &[String],
+    ) -> Option<&PageList> {
+        let Some((title, rest)) = titles.split_first() else {
+            return Some(self);
+        };
+        match self.items.get(title)? {
+            ListItem::List(submenu) => submenu.sub_list(rest),
+            ListItem::Page(_) => None,
+        }
+    }
     pub fn add_pages(
         &self,
         project: &mut Project,
@@ -56,52 +73,60 @@ impl PageList {
             }
         }
     }
+    #[deprecated(
+        since = "0.18.0",
+        note = "builds a NavIndex of the whole site map on each call, use `NavIndex::order`"
+    )]
     pub fn add_page_paths<'m>(
         &'m self,
         list: &mut Vec<&'m PagePath>,
     ) {
-        for item in self.items.values() {
-            match item {
-                ListItem::Page(path) => {
-                    if !list.contains(&path) {
-                        list.push(path);
-                    }
-                }
-                ListItem::List(submenu) => {
-                    submenu.add_page_paths(list);
-                }
+        for path in NavIndex::new(self).order() {
+            if let Some(path) = self.find_page_path(path)
+                && !list.contains(&path)
+            {
+                list.push(path);
             }
         }
     }
+    #[deprecated(
+        since = "0.18.0",
+        note = "builds a NavIndex of the whole site map on each call, use `NavIndex::previous`"
+    )]
     pub fn previous(
         &self,
         current_page: &PagePath,
     ) -> Option<&PagePath> {
-        let mut page_paths = Vec::new();
-        self.add_page_paths(&mut page_paths);
-        for (i, path) in page_paths.iter().enumerate() {
-            if path == &current_page {
-                if i > 0 {
-                    return Some(page_paths[i - 1]);
-                } else {
-                    return None;
-                }
-            }
-        }
-        None
+        let nav = NavIndex::new(self);
+        self.find_page_path(nav.previous(current_page)?)
     }
+    #[deprecated(
+        since = "0.18.0",
+        note = "builds a NavIndex of the whole site map on each call, use `NavIndex::next`"
+    )]
     pub fn next(
         &self,
         current_page: &PagePath,
     ) -> Option<&PagePath> {
-        let mut page_paths = Vec::new();
-        self.add_page_paths(&mut page_paths);
-        for (i, path) in page_paths.iter().enumerate() {
-            if path == &current_page {
-                if i + 1 < page_paths.len() {
-                    return Some(page_paths[i + 1]);
-                } else {
-                    return None;
+        let nav = NavIndex::new(self);
+        self.find_page_path(nav.next(current_page)?)
+    }
+    /// Return the occurrence, in this list, of the given page path
+    fn find_page_path(
+        &self,
+        page_path: &PagePath,
+    ) -> Option<&PagePath> {
+        for item in self.items.values() {
+            match item {
+                ListItem::Page(path) => {
+                    if path == page_path {
+                        return Some(path);
+                    }
+                }
+                ListItem::List(submenu) => {
+                    if let Some(path) = submenu.find_page_path(page_path) {
+                        return Some(path);
+                    }
                 }
             }
         }
@@ -110,11 +135,16 @@ impl PageList {
     pub fn push_nav(
         &self,
         html: &mut String,
+        id: Option<&str>,
         classes: &[ClassName],
         menu_insert: &Menu,
         hosting_page_path: &PagePath,
     ) -> DdResult<()> {
-        html.push_str("<nav class=\"site-nav");
+        html.push_str("<nav");
+        if let Some(id) = id {
+            write!(html, " id=\"{}\"", escape_attr(id))?;
+        }
+        html.push_str(" class=\"site-nav");
         for class in classes {
             html.push(' ');
             html.push_str(class.as_str());
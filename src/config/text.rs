@@ -61,7 +61,7 @@ impl From<&AttributeValue> for Text {
     fn from(value: &AttributeValue) -> Self {
         match value {
             AttributeValue::String(s) => Self::from(s.as_str()),
-            AttributeValue::Bool(b) => Self::String(b.to_string()),
+            value => Self::String(value.to_string()),
         }
     }
 }
@@ -69,7 +69,7 @@ impl From<AttributeValue> for Text {
     fn from(value: AttributeValue) -> Self {
         match value {
             AttributeValue::String(s) => Self::from(s),
-            AttributeValue::Bool(b) => Self::String(b.to_string()),
+            value => Self::String(value.to_string()),
         }
     }
 }
@@ -1,6 +1,12 @@
 use {
     crate::*,
+    schemars::{
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+    },
     std::{
+        borrow::Cow,
         fmt,
         str::FromStr,
     },
@@ -18,6 +24,24 @@ pub struct Toc {
     pub activate_visible_item: bool,
 }
 
+impl Toc {
+    /// The attributes of a `ddoc-toc` element
+    pub const ATTRIBUTES: &[AttributeDef] = &[
+        AttributeDef::new(
+            "title",
+            AttributeType::String,
+            "Title of the table of content, the page title if not specified",
+        ),
+        AttributeDef::new(
+            "activate-visible-item",
+            AttributeType::Boolean,
+            "Deprecated: enable the toc-activate plugin instead",
+        )
+        .deprecated(),
+        AttributeDef::alias("activate_visible_item", AttributeType::Boolean),
+    ];
+}
+
 impl FromStr for Toc {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -37,15 +61,14 @@ impl From<Attributes> for Toc {
         if let Some(v) = map
             .get("activate-visible-item")
             .or_else(|| map.get("activate_visible_item"))
+            && let Some(b) = v.as_bool()
         {
-            if let Some(b) = v.as_bool() {
-                toc_insert.activate_visible_item = b;
-            }
+            toc_insert.activate_visible_item = b;
         }
-        if let Some(v) = map.get("title") {
-            if let Some(s) = v.as_str() {
-                toc_insert.title = Some(s.to_string());
-            }
+        if let Some(v) = map.get("title")
+            && let Some(s) = v.as_str()
+        {
+            toc_insert.title = Some(s.to_string());
         }
         toc_insert
     }
@@ -73,3 +96,14 @@ impl<'de> serde::Deserialize<'de> for Toc {
         s.parse().map_err(serde::de::Error::custom)
     }
 }
+impl JsonSchema for Toc {
+    fn schema_name() -> Cow<'static, str> {
+        "Toc".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        element_schema(
+            "A ddoc-toc element, the table of content of the current page",
+            Self::ATTRIBUTES,
+        )
+    }
+}
@@ -0,0 +1,214 @@
+use {
+    crate::*,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::{
+        fmt,
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+    termimad::crossterm::style::Stylize,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Where a problem was found: a file, and a position in this file when known
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: Option<usize>,
+    /// 1-based column number, in chars
+    pub column: Option<usize>,
+}
+
+/// A problem found in the project, with enough context to be fixed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+    /// The page whose rendering revealed the problem, if any
+    pub page: Option<PagePath>,
+    /// An optional suggestion on how to fix the problem
+    pub help: Option<String>,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl Location {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            line: None,
+            column: None,
+        }
+    }
+    pub fn with_position(
+        mut self,
+        line: usize,
+        column: usize,
+    ) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+    /// Build the location of the byte at `offset` in `text`, which is
+    /// the content of the file at `path`
+    pub fn at_offset<P: Into<PathBuf>>(
+        path: P,
+        text: &str,
+        offset: usize,
+    ) -> Self {
+        let (line, column) = line_column(text, offset);
+        Self::new(path).with_position(line, column)
+    }
+    /// Write the location, with the path relative to `root` when possible
+    pub fn to_string_relative(
+        &self,
+        root: &Path,
+    ) -> String {
+        let path = self.path.strip_prefix(root).unwrap_or(&self.path);
+        let mut s = path.to_string_lossy().to_string();
+        if s.is_empty() {
+            s.push('.'); // the root itself
+        }
+        if let Some(line) = self.line {
+            s.push_str(&format!(":{line}"));
+            if let Some(column) = self.column {
+                s.push_str(&format!(":{column}"));
+            }
+        }
+        s
+    }
+}
+
+impl Diagnostic {
+    pub fn new<S: Into<String>>(
+        severity: Severity,
+        message: S,
+    ) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            location: None,
+            page: None,
+            help: None,
+        }
+    }
+    pub fn error<S: Into<String>>(message: S) -> Self {
+        Self::new(Severity::Error, message)
+    }
+    pub fn warning<S: Into<String>>(message: S) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+    pub fn at(
+        mut self,
+        location: Location,
+    ) -> Self {
+        self.location = Some(location);
+        self
+    }
+    pub fn in_page(
+        mut self,
+        page_path: PagePath,
+    ) -> Self {
+        self.page = Some(page_path);
+        self
+    }
+    pub fn with_help<S: Into<String>>(
+        mut self,
+        help: S,
+    ) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+    /// Print the diagnostic to stderr, with paths relative to `root`
+    pub fn print(
+        &self,
+        root: &Path,
+    ) {
+        match self.severity {
+            Severity::Error => eprint!("{}", "error".red().bold()),
+            Severity::Warning => eprint!("{}", "warning".yellow().bold()),
+        }
+        eprintln!(": {}", self.message);
+        if let Some(location) = &self.location {
+            eprintln!("  {} {}", "-->".blue(), location.to_string_relative(root));
+        }
+        if let Some(page) = &self.page {
+            eprintln!("  {} {}", "page:".blue(), page);
+        }
+        if let Some(help) = &self.help {
+            eprintln!("  {} {}", "help:".green(), help);
+        }
+    }
+}
+
+/// Print the count of errors and warnings, eg "Build found 1 error(s) and
+/// 2 warning(s)", or that there's no problem
+pub fn print_diagnostics_summary(
+    what: &str, // eg "Build" or "Check"
+    diagnostics: &[Diagnostic],
+) {
+    if diagnostics.is_empty() {
+        eprintln!("{}", "No problem found".green().bold());
+        return;
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    eprintln!(
+        "{what} found {} and {}",
+        format!("{errors} error(s)").red().bold(),
+        format!("{} warning(s)", diagnostics.len() - errors)
+            .yellow()
+            .bold(),
+    );
+}
+
+/// Compute the 1-based line and column (in chars) of the byte at `offset` in `text`
+pub fn line_column(
+    text: &str,
+    offset: usize,
+) -> (usize, usize) {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[test]
+fn test_line_column() {
+    let text = "ab\ncdé\nf";
+    assert_eq!(line_column(text, 0), (1, 1));
+    assert_eq!(line_column(text, 3), (2, 1));
+    assert_eq!(line_column(text, 7), (2, 4));
+    assert_eq!(line_column(text, 8), (3, 1));
+    assert_eq!(line_column(text, 100), (3, 2));
+}
@@ -0,0 +1,475 @@
+use {
+    crate::*,
+    pulldown_cmark::{
+        self as pcm,
+        CowStr,
+        Event,
+        HeadingLevel,
+        Parser,
+        Tag,
+        TagEnd,
+        html::push_html,
+    },
+    rustc_hash::{
+        FxHashMap,
+        FxHashSet,
+    },
+    std::{
+        fmt::Write as _,
+        fs,
+        io::{
+            Seek,
+            Write,
+        },
+        path::PathBuf,
+        time::SystemTime,
+    },
+    zip::{
+        CompressionMethod,
+        ZipWriter,
+        write::SimpleFileOptions,
+    },
+};
+
+/// Directory of the content in the EPUB archive
+static OEBPS: &str = "OEBPS";
+
+static CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles>
+<rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+</rootfiles>
+</container>
+"#;
+
+/// A page of the site, as a document of the EPUB
+struct EpubPage<'p> {
+    page: &'p Page,
+    /// Name of the XHTML file, relative to the OEBPS directory
+    file_name: String,
+    md: String,
+}
+
+impl Project {
+    /// The path of the EPUB file written by `ddoc --export epub`: in the
+    /// project directory, named after the title of the site
+    pub fn epub_path(&self) -> PathBuf {
+        let mut name = String::new();
+        for c in self.config.title().chars() {
+            if c.is_alphanumeric() {
+                name.extend(c.to_lowercase());
+            } else if !name.is_empty() && !name.ends_with('-') {
+                name.push('-');
+            }
+        }
+        let name = name.trim_end_matches('-');
+        let name = if name.is_empty() { "site" } else { name };
+        self.root.join(format!("{name}.epub"))
+    }
+
+    /// Package the pages of the site, in the order of the site map, with
+    /// their images, as an EPUB 3 file
+    ///
+    /// Raw HTML of the pages isn't included, as it's not guaranteed to be
+    /// valid XHTML, and images which can't be embedded are replaced with
+    /// their alt text.
+    ///
+    /// # Errors
+    /// Return `DdError::Zip` or `DdError::Io` if the archive can't be written
+    pub fn write_epub<W: Write + Seek>(
+        &self,
+        writer: W,
+    ) -> DdResult<()> {
+        let mut zip = ZipWriter::new(writer);
+        // the mimetype must be the first entry, and stored uncompressed
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(b"application/epub+zip")?;
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file("META-INF/container.xml", options)?;
+        zip.write_all(CONTAINER_XML.as_bytes())?;
+
+        let mut pages = Vec::new();
+        for page_path in self.nav().order() {
+            let Some(page) = self.pages.get(page_path) else {
+                continue;
+            };
+            let Ok(md) = fs::read_to_string(&page.md_file_path) else {
+                continue;
+            };
+            pages.push(EpubPage {
+                page,
+                file_name: epub_file_name(page_path),
+                md,
+            });
+        }
+        let static_files = self.static_files()?;
+        let mut images = FxHashMap::default();
+        for page in &pages {
+            let xhtml = self.epub_page_xhtml(page, &static_files, &mut images)?;
+            zip.start_file(format!("{OEBPS}/{}", page.file_name), options)?;
+            zip.write_all(xhtml.as_bytes())?;
+        }
+        let mut images: Vec<(String, PathBuf)> = images.into_iter().collect();
+        images.sort();
+        for (path, src_path) in &images {
+            zip.start_file(format!("{OEBPS}/{path}"), options)?;
+            zip.write_all(&fs::read(src_path)?)?;
+        }
+        zip.start_file(format!("{OEBPS}/nav.xhtml"), options)?;
+        zip.write_all(self.epub_nav_xhtml()?.as_bytes())?;
+        zip.start_file(format!("{OEBPS}/content.opf"), options)?;
+        zip.write_all(self.epub_package_opf(&pages, &images)?.as_bytes())?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// Build the XHTML document of a page, registering the images it embeds
+    fn epub_page_xhtml(
+        &self,
+        page: &EpubPage,
+        static_files: &FxHashMap<String, PathBuf>,
+        images: &mut FxHashMap<String, PathBuf>,
+    ) -> DdResult<String> {
+        let page_path = &page.page.page_path;
+        let mut id_counts = FxHashMap::default();
+        let mut events = Vec::new();
+        let mut in_dropped_image = false;
+        let parser = Parser::new_ext(&page.md, pcm::Options::all());
+        for mut event in parser {
+            match &mut event {
+                Event::Html(_) | Event::InlineHtml(_) => continue,
+                Event::Start(Tag::Image { dest_url, .. }) => {
+                    match self.epub_img_path(dest_url, static_files) {
+                        Some((path, src_path)) => {
+                            *dest_url = CowStr::from(path.clone());
+                            images.insert(path, src_path);
+                        }
+                        None => {
+                            in_dropped_image = true;
+                            continue;
+                        }
+                    }
+                }
+                Event::End(TagEnd::Image) if in_dropped_image => {
+                    in_dropped_image = false;
+                    continue;
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    if let Some(url) = self.epub_link_url(dest_url, page_path) {
+                        *dest_url = CowStr::from(url);
+                    }
+                }
+                _ => {}
+            }
+            events.push(event);
+        }
+        add_heading_ids(&mut events, &mut id_counts);
+        let title = escape_text(&page.page.title);
+        let mut xhtml = xhtml_start(&title, self.config.language());
+        xhtml.push_str("<section epub:type=\"chapter\">\n");
+        if !starts_with_h1(&events) {
+            writeln!(xhtml, "<h1>{title}</h1>")?;
+        }
+        push_html(&mut xhtml, events.into_iter());
+        xhtml.push_str("</section>\n</body>\n</html>\n");
+        Ok(xhtml)
+    }
+
+    /// Return the path in the EPUB of an image of a page, and the path of
+    /// its file, if it can be embedded
+    fn epub_img_path(
+        &self,
+        src: &str,
+        static_files: &FxHashMap<String, PathBuf>,
+    ) -> Option<(String, PathBuf)> {
+        let (_, path) = self.img_src_path(src)?;
+        image_media_type(&path)?;
+        let src_path = static_files.get(&path)?.clone();
+        Some((path, src_path))
+    }
+
+    /// Rewrite a link to a page of the site as a link to its document
+    fn epub_link_url(
+        &self,
+        src: &str,
+        page_path: &PagePath,
+    ) -> Option<String> {
+        let target = self.internal_link_target(src, page_path)?;
+        if !self.pages.contains_key(&target) {
+            return None;
+        }
+        let file_name = epub_file_name(&target);
+        Some(match src.split_once('#') {
+            Some((_, hash)) if !hash.is_empty() => format!("{file_name}#{hash}"),
+            _ => file_name,
+        })
+    }
+
+    /// Build the navigation document, with the structure of the site map
+    fn epub_nav_xhtml(&self) -> DdResult<String> {
+        let mut xhtml = xhtml_start(&escape_text(self.config.title()), self.config.language());
+        xhtml.push_str("<nav epub:type=\"toc\" id=\"toc\">\n");
+        writeln!(xhtml, "<h1>{}</h1>", escape_text(self.config.title()))?;
+        let mut listed = FxHashSet::default();
+        write_epub_toc(&mut xhtml, &self.config.site_map, &mut listed)?;
+        xhtml.push_str("</nav>\n</body>\n</html>\n");
+        Ok(xhtml)
+    }
+
+    /// Build the package document: metadata, list of files, reading order
+    fn epub_package_opf(
+        &self,
+        pages: &[EpubPage],
+        images: &[(String, PathBuf)],
+    ) -> DdResult<String> {
+        let identifier = match self.config.base_url() {
+            Some(url) => url.to_string(),
+            None => format!(
+                "urn:ddoc:{:016x}",
+                xxhash_rust::xxh3::xxh3_64(self.config.title().as_bytes())
+            ),
+        };
+        let mut opf = String::new();
+        opf.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        opf.push('\n');
+        opf.push_str(
+            r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id">"#,
+        );
+        opf.push('\n');
+        opf.push_str(r#"<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">"#);
+        opf.push('\n');
+        writeln!(
+            opf,
+            r#"<dc:identifier id="book-id">{}</dc:identifier>"#,
+            escape_text(&identifier),
+        )?;
+        writeln!(
+            opf,
+            "<dc:title>{}</dc:title>",
+            escape_text(self.config.title())
+        )?;
+        writeln!(
+            opf,
+            "<dc:language>{}</dc:language>",
+            escape_text(self.config.language()),
+        )?;
+        if let Some(description) = self.config.description() {
+            writeln!(
+                opf,
+                "<dc:description>{}</dc:description>",
+                escape_text(description),
+            )?;
+        }
+        writeln!(
+            opf,
+            r#"<meta property="dcterms:modified">{}</meta>"#,
+            utc_timestamp(SystemTime::now()),
+        )?;
+        opf.push_str("</metadata>\n<manifest>\n");
+        opf.push_str(
+            r#"<item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>"#,
+        );
+        opf.push('\n');
+        for (i, page) in pages.iter().enumerate() {
+            writeln!(
+                opf,
+                r#"<item id="page-{i}" href="{}" media-type="application/xhtml+xml"/>"#,
+                escape_attr(&page.file_name),
+            )?;
+        }
+        for (i, (path, _)) in images.iter().enumerate() {
+            writeln!(
+                opf,
+                r#"<item id="img-{i}" href="{}" media-type="{}"/>"#,
+                escape_attr(path),
+                image_media_type(path).unwrap_or_default(),
+            )?;
+        }
+        opf.push_str("</manifest>\n<spine>\n");
+        for i in 0..pages.len() {
+            writeln!(opf, r#"<itemref idref="page-{i}"/>"#)?;
+        }
+        opf.push_str("</spine>\n</package>\n");
+        Ok(opf)
+    }
+}
+
+/// The name of the XHTML document of a page, eg `guide-setup.xhtml`
+fn epub_file_name(page_path: &PagePath) -> String {
+    format!("{}.xhtml", page_path.to_string().replace('/', "-"))
+}
+
+/// Tell whether the content of a page, after its front matter, starts
+/// with a level 1 heading, which can then serve as title of the chapter
+fn starts_with_h1(events: &[Event]) -> bool {
+    let mut in_front_matter = false;
+    for event in events {
+        match event {
+            Event::Start(Tag::MetadataBlock(_)) => in_front_matter = true,
+            Event::End(TagEnd::MetadataBlock(_)) => in_front_matter = false,
+            _ if in_front_matter => {}
+            Event::Start(Tag::Heading { level, .. }) => return *level == HeadingLevel::H1,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// The media type of an image, if it's one of the core media types of EPUB 3
+fn image_media_type(path: &str) -> Option<&'static str> {
+    let (_, ext) = path.rsplit_once('.')?;
+    match ext.to_ascii_lowercase().as_str() {
+        "gif" => Some("image/gif"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        "svg" => Some("image/svg+xml"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// The start of an XHTML document, up to the opening `<body>` tag
+fn xhtml_start(
+    escaped_title: &str,
+    language: &str,
+) -> String {
+    let language = escape_attr(language);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{language}" xml:lang="{language}">
+<head>
+<meta charset="utf-8"/>
+<title>{escaped_title}</title>
+</head>
+<body>
+"#
+    )
+}
+
+/// Write the list of the navigation document, skipping the pages already
+/// listed and the sub menus without page
+fn write_epub_toc(
+    xhtml: &mut String,
+    list: &PageList,
+    listed: &mut FxHashSet<PagePath>,
+) -> DdResult<()> {
+    let mut items = String::new();
+    for (title, item) in &list.items {
+        let title = escape_text(title);
+        match item {
+            ListItem::Page(page_path) if listed.insert(page_path.clone()) => {
+                writeln!(
+                    items,
+                    r#"<li><a href="{}">{title}</a></li>"#,
+                    epub_file_name(page_path),
+                )?;
+            }
+            ListItem::Page(_) => {}
+            ListItem::List(submenu) => {
+                let mut sub_items = String::new();
+                write_epub_toc(&mut sub_items, submenu, listed)?;
+                if !sub_items.is_empty() {
+                    writeln!(items, "<li><span>{title}</span>\n{sub_items}</li>")?;
+                }
+            }
+        }
+    }
+    if !items.is_empty() {
+        writeln!(xhtml, "<ol>\n{items}</ol>")?;
+    }
+    Ok(())
+}
+
+/// Format a time as required for `dcterms:modified`, eg `2025-03-01T12:00:00Z`
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+    // civil date from days since 1970-01-01, after Howard Hinnant
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+    )
+}
+
+#[test]
+fn test_utc_timestamp() {
+    use std::time::Duration;
+    let at = |secs| utc_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+    assert_eq!(at(0), "1970-01-01T00:00:00Z");
+    assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+    assert_eq!(at(1_760_873_045), "2025-10-19T11:24:05Z");
+}
+
+#[test]
+fn test_write_epub() {
+    use std::io::{
+        Cursor,
+        Read,
+    };
+    let test_project = TestProject::new("epub");
+    test_project
+        .write("src/index.md", "See [the setup](guide/setup.md#install).\n")
+        .write(
+            "src/guide/setup.md",
+            "---\ndescription: How to install\n---\n# Install\n\n![logo](../img/logo.png) <b>raw</b>\n",
+        )
+        .write("src/img/logo.png", b"not really a png");
+    let project = test_project.load(
+        r#"{
+            title: Test Book
+            language: fr
+            site-map: {
+                Home: index.md
+                Guide: {
+                    Setup: guide/setup.md
+                }
+            }
+        }"#,
+    );
+    assert_eq!(
+        project.epub_path(),
+        test_project.root.join("test-book.epub")
+    );
+    let mut cursor = Cursor::new(Vec::new());
+    project.write_epub(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    // the mimetype entry must come first, uncompressed, right after its header
+    assert_eq!(&bytes[30..38], b"mimetype");
+    assert_eq!(&bytes[38..58], b"application/epub+zip");
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut text = String::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).unwrap();
+        if file.name() != "mimetype" {
+            assert_eq!(file.compression(), CompressionMethod::Deflated);
+        }
+        file.read_to_string(&mut text).unwrap();
+    }
+    assert!(text.contains(r#"<li><a href="guide-setup.xhtml">Setup</a></li>"#));
+    assert!(text.contains(r#"<a href="guide-setup.xhtml#install">the setup</a>"#));
+    assert!(text.contains(r#"<h1 id="install">Install</h1>"#));
+    // the title is added only to the pages not starting with a level 1 heading
+    assert!(text.contains("<h1>Home</h1>"));
+    assert!(!text.contains("<h1>Setup</h1>"));
+    assert!(text.contains(r#"<img src="img/logo.png""#));
+    assert!(text.contains(r#"href="img/logo.png" media-type="image/png""#));
+    assert!(!text.contains("<b>raw</b>"));
+    assert!(text.contains("<dc:language>fr</dc:language>"));
+    assert!(text.contains(r#"lang="fr" xml:lang="fr""#));
+}
@@ -4,6 +4,11 @@ pub enum DdError {
     AbsolutePath { path: std::path::PathBuf },
     #[error("Config file not found")]
     ConfigNotFound,
+    #[error("Invalid build directory {path:?}: {reason}")]
+    InvalidBuildDir {
+        path: std::path::PathBuf,
+        reason: String,
+    },
     #[error("Invalid config")]
     InvalidConfig,
     #[error("Fmt Error: {0}")]
@@ -14,12 +19,18 @@ pub enum DdError {
     InitNotPossible(String),
     #[error("Internal error: {0}")]
     Internal(String),
+    #[error("Invalid element key {key:?}: {reason}")]
+    InvalidElementKey { key: String, reason: String },
+    #[error("Invalid glob {glob:?}: {reason}")]
+    InvalidGlob { glob: String, reason: String },
     #[error("Invalid page path: {path}")]
     InvalidPagePath { path: String },
     #[error("IO Error: {0}")]
     Io(#[from] std::io::Error),
     #[error("JSON Error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Several config files found: {paths:?}")]
+    MultipleConfigFiles { paths: Vec<std::path::PathBuf> },
     #[error("Plugin not found: {name}")]
     PluginNotFound { name: String },
     #[error("Plugin not in site: {name}")]
@@ -37,6 +48,8 @@ pub enum DdError {
     UnsupportedFileFormat(std::path::PathBuf),
     #[error("Invalid UTF-8: {0}")]
     Utf8(#[from] std::str::Utf8Error),
+    #[error("Zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
 }
 
 pub type DdResult<T> = Result<T, DdError>;
@@ -7,10 +7,31 @@ use {
             File,
         },
         io::BufReader,
-        path::Path,
+        path::{
+            Component,
+            Path,
+            PathBuf,
+        },
     },
 };
 
+/// Make a path absolute and remove its `.` and `..` components, without
+/// accessing the file system (the path doesn't have to exist)
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
 /// Deserialize an object from a JSON, TOML, or HJSON file.
 ///
 /// # Errors
@@ -40,7 +61,7 @@ where
 }
 
 /// Search direct subdirectories of `parent` for a ddoc project
-/// (a directory containing a valid `ddoc.hjson` file).
+/// (a directory containing a valid `ddoc.hjson`, `ddoc.toml`, or `ddoc.json` file).
 pub fn project_subdirectory(parent: &Path) -> Option<std::path::PathBuf> {
     let read_dir = std::fs::read_dir(parent).ok()?;
     for entry in read_dir.flatten() {
@@ -52,6 +73,33 @@ pub fn project_subdirectory(parent: &Path) -> Option<std::path::PathBuf> {
     None
 }
 
+/// List normal non hidden files of `src_dir` recursively, with their path
+/// relative to `src_dir` prefixed with `rel_prefix`
+pub fn list_normal_files_recursive(
+    src_dir: &Path,
+    rel_prefix: &str,
+    files: &mut Vec<(String, PathBuf)>,
+) -> DdResult<()> {
+    for entry in fs::read_dir(src_dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let sub_prefix = format!("{rel_prefix}{file_name}/");
+            list_normal_files_recursive(&entry.path(), &sub_prefix, files)?;
+            continue;
+        }
+        if !file_type.is_file() || file_name.starts_with('.') {
+            continue;
+        }
+        files.push((format!("{rel_prefix}{file_name}"), entry.path()));
+    }
+    Ok(())
+}
+
 /// Copy normal non hidden files from `src_dir` to `dst_dir` recursively
 pub fn copy_normal_recursive(
     src_dir: &Path,
@@ -0,0 +1,66 @@
+use {
+    indexmap::IndexMap,
+    lazy_regex::regex_captures,
+};
+
+/// The properties defined in the front matter of a markdown page, that is
+/// the `key: value` lines of a block delimited by `---` lines at the very
+/// top of the file.
+///
+/// Only single line values are supported.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrontMatter {
+    properties: IndexMap<String, String>,
+}
+
+impl FrontMatter {
+    pub fn parse(md: &str) -> Self {
+        let mut properties = IndexMap::default();
+        let mut lines = md.lines();
+        if lines.next().map(str::trim_end) == Some("---") {
+            for line in lines {
+                let line = line.trim_end();
+                if line == "---" || line == "..." {
+                    break;
+                }
+                if let Some((_, key, value)) = regex_captures!(r"^([\w-]+)\s*:\s*(.*)$", line) {
+                    let value = value
+                        .strip_prefix('"')
+                        .and_then(|v| v.strip_suffix('"'))
+                        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                        .unwrap_or(value);
+                    properties.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        Self { properties }
+    }
+    pub fn get(
+        &self,
+        key: &str,
+    ) -> Option<&str> {
+        self.properties
+            .get(key)
+            .map(String::as_str)
+            .filter(|s| !s.is_empty())
+    }
+    pub fn description(&self) -> Option<&str> {
+        self.get("description")
+    }
+    pub fn image(&self) -> Option<&str> {
+        self.get("image")
+    }
+}
+
+#[test]
+fn test_parse_front_matter() {
+    let md = "---\ndescription: \"A short: description\"\nimage: img/a.png\nempty:\n---\n# Title\n";
+    let front_matter = FrontMatter::parse(md);
+    assert_eq!(front_matter.description(), Some("A short: description"));
+    assert_eq!(front_matter.get("image"), Some("img/a.png"));
+    assert_eq!(front_matter.get("empty"), None);
+    assert_eq!(
+        FrontMatter::parse("# Title\n\ndescription: no").description(),
+        None
+    );
+}
@@ -0,0 +1,60 @@
+use {
+    crate::*,
+    std::process::Command,
+};
+
+impl Project {
+    /// Run the hook commands of the given stage, in order, stopping at
+    /// the first failing one, and return the problems met
+    ///
+    /// The commands are run by the shell, with the project root as current
+    /// directory, and environment variables describing the build.
+    pub fn run_hooks(
+        &self,
+        stage: HookStage,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for command in self.config.hooks.commands(stage) {
+            info!("running {stage} hook {command:?}");
+            let problem = match self.hook_command(command, stage).status() {
+                Ok(status) if status.success() => continue,
+                Ok(status) => match status.code() {
+                    Some(code) => format!("{stage} hook failed with exit code {code}: {command}"),
+                    None => format!("{stage} hook was interrupted: {command}"),
+                },
+                Err(e) => format!("{stage} hook could not be run ({e}): {command}"),
+            };
+            let mut diagnostic = Diagnostic::error(problem);
+            if let Some(location) = self.locate_in_config(&["hooks", command]) {
+                diagnostic = diagnostic.at(location);
+            }
+            diagnostics.push(diagnostic);
+            break;
+        }
+        diagnostics
+    }
+    fn hook_command(
+        &self,
+        command: &str,
+        stage: HookStage,
+    ) -> Command {
+        let mut cmd = if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C");
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c");
+            cmd
+        };
+        cmd.arg(command)
+            .current_dir(&self.root)
+            .env("DDOC_HOOK", stage.to_string())
+            .env("DDOC_VERSION", DDOC_VERSION)
+            .env("DDOC_ROOT", &self.root)
+            .env("DDOC_SRC_DIR", &self.src_path)
+            .env("DDOC_BUILD_DIR", &self.build_path)
+            .env("DDOC_MINIFY", self.minify().to_string());
+        cmd
+    }
+}
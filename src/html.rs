@@ -1,6 +1,5 @@
-pub static HTML_START: &str = r#"<!DOCTYPE html>
-<html lang="en">
-<head>
+/// The start of the `<head>` of a page, which follows the `<html>` tag
+pub static HTML_HEAD_START: &str = r#"<head>
 <meta charset="utf-8">
 <meta http-equiv="X-UA-Compatible" content="IE=edge">
 <meta name="viewport" content="width=device-width, initial-scale=1, minimum-scale=1"/>
@@ -96,18 +96,18 @@ site-map: {
 "#;
 
 /// Initialize a ddoc.hjson file in the specified directory
-/// (do nothing if one already exists)
+/// (do nothing if a config file, hjson, toml, or json, already exists)
 ///
 /// # Errors
-/// Return `DdError::InvalidConfig` if an existing ddoc.hjson
-/// cannot be read, or other less likely `DdError` variants on
+/// Return `DdError::InvalidConfig` if an existing config file
+/// cannot be read, `DdError::MultipleConfigFiles` if there are several
+/// ones, or other less likely `DdError` variants on
 /// write errors when creating a new ddoc.hjson
 pub fn init_hjson_in_dir(
     dir: &Path,
     init_values: &InitValues,
 ) -> DdResult<Config> {
-    let path = dir.join("ddoc.hjson");
-    if path.exists() {
+    if let Some(path) = Config::path_in_dir(dir)? {
         read_file(&path).map_err(|e| {
             error!("Error reading {}: {}", path.display(), e);
             // Return a specific error so that the caller can
@@ -134,6 +134,7 @@ pub fn init_hjson_in_dir(
             .replace("<ddoc-version>", DDOC_VERSION)
             .replace("<vars>", &vars);
 
+        let path = dir.join(CONFIG_FILE_NAME);
         fs::write(&path, hjson)?;
         eprintln!("Created {}", path.display());
         read_file(&path)
@@ -25,19 +25,19 @@ impl InitValues {
             if init_values.title.is_none() {
                 init_values.title = Some(cargo_toml.project_name().to_owned());
             }
-            if init_values.description.is_none() {
-                if let Some(desc) = cargo_toml.project_description() {
-                    init_values.description = Some(desc.to_owned());
-                }
+            if init_values.description.is_none()
+                && let Some(desc) = cargo_toml.project_description()
+            {
+                init_values.description = Some(desc.to_owned());
             }
             if init_values.github_repo.is_none() {
                 init_values.github_repo = cargo_toml.github_repository();
             }
         }
-        if let Some(readme_path) = find_file_ignore_case(parent_dir, "README.md") {
-            if init_values.index.is_none() {
-                init_values.index = Some(readme_path);
-            }
+        if let Some(readme_path) = find_file_ignore_case(parent_dir, "README.md")
+            && init_values.index.is_none()
+        {
+            init_values.index = Some(readme_path);
         }
         init_values.plugins = EmbeddedPlugin::all();
         Ok(init_values)
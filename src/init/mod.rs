@@ -56,14 +56,19 @@ pub fn init_ddoc_project(dir: &Path) -> DdResult<()> {
         }
     }
 
-    // ddoc.hjson
+    // ddoc.hjson (or ddoc.toml or ddoc.json)
     let config = match init_hjson_in_dir(&dir, &init_values) {
         Err(DdError::InvalidConfig) => {
             return Err(DdError::InitNotPossible(
-                "ddoc.hjson already exists but is invalid. Please delete or fix it first"
+                "The config file already exists but is invalid. Please delete or fix it first"
                     .to_string(),
             ));
         }
+        Err(DdError::MultipleConfigFiles { paths }) => {
+            return Err(DdError::InitNotPossible(format!(
+                "Several config files found: {paths:?}. Please keep only one"
+            )));
+        }
         res => res?,
     };
 
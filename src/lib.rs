@@ -21,6 +21,7 @@
 //!
 //! - a `.gitignore` file, which eases inclusion of your site in a git managed project
 //! - a `ddoc.hjson` file, holding the basic properties and navigation
+//!   (a `ddoc.toml` or `ddoc.json` file may be used instead)
 //! - a `src` folder, for your markdown files, CSS style sheets and images
 //! - a `plugins` folder, with default plugins
 //!
@@ -41,39 +42,71 @@
 //! Then have a look at the [documentation](https://dystroy.org/ddoc/setup/) to improve your site.
 //!
 
+mod asset_fingerprints;
+mod build_manifest;
+mod build_result;
+mod bundle;
+mod check;
 mod cli;
 mod compat;
 mod config;
+mod diagnostic;
+mod epub;
 mod error;
 mod files;
+mod front_matter;
+mod hooks;
 mod html;
 mod init;
+mod llms_txt;
+mod minify;
 mod module;
+mod nav_index;
+mod output_sink;
 mod page;
 mod page_path;
 mod page_writer;
+mod print_page;
 mod project;
+mod project_builder;
 mod resources;
+mod search_index;
 mod server;
 mod sourced;
 mod statics;
+#[cfg(test)]
+mod test_util;
 mod version;
 mod watcher;
 
 pub use {
+    asset_fingerprints::*,
+    build_manifest::*,
+    build_result::*,
+    bundle::*,
+    check::*,
     cli::*,
     compat::*,
     config::*,
+    diagnostic::*,
     error::*,
     files::*,
+    front_matter::*,
     html::*,
     init::*,
+    llms_txt::*,
+    minify::*,
     module::*,
+    nav_index::*,
+    output_sink::*,
     page::*,
     page_path::*,
     page_writer::*,
+    print_page::*,
     project::*,
+    project_builder::*,
     resources::*,
+    search_index::*,
     server::*,
     sourced::*,
     statics::*,
@@ -81,5 +114,8 @@ pub use {
     watcher::*,
 };
 
+#[cfg(test)]
+pub use test_util::*;
+
 #[macro_use]
 extern crate cli_log;
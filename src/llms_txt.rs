@@ -0,0 +1,359 @@
+use {
+    crate::*,
+    pulldown_cmark::{
+        self as pcm,
+        Event,
+        Parser,
+        Tag,
+        TagEnd,
+    },
+    rustc_hash::FxHashSet,
+    std::{
+        fmt::Write,
+        fs,
+        ops::Range,
+        path::Path,
+    },
+};
+
+/// Name of the index of the site for language models, built when
+/// `llms-txt` is set
+pub static LLMS_TXT_FILE_NAME: &str = "llms.txt";
+
+/// Name of the file with the Markdown of all pages, built when
+/// `llms-txt` is set
+pub static LLMS_FULL_TXT_FILE_NAME: &str = "llms-full.txt";
+
+impl Project {
+    /// The path of the copy of the Markdown of a page, next to its
+    /// HTML, eg `guide/setup/index.md`
+    pub fn page_md_copy_path(page_path: &PagePath) -> String {
+        page_path
+            .html_path_buf(Path::new(""))
+            .with_extension("md")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// The URL of a file of the site, given its path relative to the
+    /// site root: absolute when there's a base URL, starting with the
+    /// base path otherwise
+    fn llms_url(
+        &self,
+        path: &str,
+    ) -> String {
+        let path = path.trim_start_matches('/');
+        self.config
+            .absolute_url(path)
+            .unwrap_or_else(|| format!("{}{}", self.config.base_path(), path))
+    }
+
+    /// Build `llms.txt`, with the title and description of the site, and
+    /// links to the Markdown of the pages, in sections following the
+    /// site map
+    ///
+    /// # Errors
+    /// Return `DdError` variants on write errors, not on project config/data errors
+    pub fn write_llms_txt(
+        &self,
+        txt: &mut String,
+    ) -> DdResult<()> {
+        writeln!(txt, "# {}\n", self.config.title())?;
+        if let Some(description) = self.config.description() {
+            writeln!(txt, "> {description}\n")?;
+        }
+        let mut listed = FxHashSet::default();
+        let mut top_pages = String::new();
+        let mut sections = Vec::new();
+        for (title, item) in &self.config.site_map.items {
+            match item {
+                ListItem::Page(page_path) => {
+                    self.write_llms_txt_link(&mut top_pages, title, page_path, 0, &mut listed)?;
+                }
+                ListItem::List(submenu) => {
+                    let mut links = String::new();
+                    self.write_llms_txt_links(&mut links, submenu, 0, &mut listed)?;
+                    if !links.is_empty() {
+                        sections.push(format!("## {title}\n\n{links}"));
+                    }
+                }
+            }
+        }
+        if !top_pages.is_empty() {
+            sections.insert(0, format!("## Pages\n\n{top_pages}"));
+        }
+        txt.push_str(&sections.join("\n"));
+        Ok(())
+    }
+
+    fn write_llms_txt_links(
+        &self,
+        txt: &mut String,
+        list: &PageList,
+        depth: usize,
+        listed: &mut FxHashSet<PagePath>,
+    ) -> DdResult<()> {
+        for (title, item) in &list.items {
+            match item {
+                ListItem::Page(page_path) => {
+                    self.write_llms_txt_link(txt, title, page_path, depth, listed)?;
+                }
+                ListItem::List(submenu) => {
+                    let mut links = String::new();
+                    self.write_llms_txt_links(&mut links, submenu, depth + 1, listed)?;
+                    if !links.is_empty() {
+                        writeln!(txt, "{:indent$}- {title}", "", indent = depth * 2)?;
+                        txt.push_str(&links);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the link to the Markdown of a page, unless it was already listed
+    fn write_llms_txt_link(
+        &self,
+        txt: &mut String,
+        title: &str,
+        page_path: &PagePath,
+        depth: usize,
+        listed: &mut FxHashSet<PagePath>,
+    ) -> DdResult<()> {
+        if !self.pages.contains_key(page_path) || !listed.insert(page_path.clone()) {
+            return Ok(());
+        }
+        writeln!(
+            txt,
+            "{:indent$}- [{title}]({})",
+            "",
+            self.llms_url(&Self::page_md_copy_path(page_path)),
+            indent = depth * 2,
+        )?;
+        Ok(())
+    }
+
+    /// Build `llms-full.txt`, with the Markdown of all pages, in the order
+    /// of the site map, without their front matter, and with the links to
+    /// pages and images made absolute
+    ///
+    /// # Errors
+    /// Return `DdError` variants on write errors, not on project config/data errors
+    pub fn write_llms_full_txt(
+        &self,
+        txt: &mut String,
+    ) -> DdResult<()> {
+        writeln!(txt, "# {}", self.config.title())?;
+        if let Some(description) = self.config.description() {
+            writeln!(txt, "\n> {description}")?;
+        }
+        for page_path in self.nav().order() {
+            let Some(page) = self.pages.get(page_path) else {
+                continue;
+            };
+            let Ok(md) = fs::read_to_string(&page.md_file_path) else {
+                continue; // reported when rendering the page itself
+            };
+            let url = self.llms_url(&page_path.to_absolute_url(""));
+            writeln!(txt, "\n---\n\n<!-- {url} -->\n")?;
+            txt.push_str(self.absolutize_md_links(&md, page_path).trim());
+            txt.push('\n');
+        }
+        Ok(())
+    }
+
+    /// Rewrite the Markdown of a page with absolute URLs for the links
+    /// to pages and images, and without the front matter
+    ///
+    /// The destinations are replaced where they are in the source, as
+    /// their parsed value may differ (eg when escaped)
+    fn absolutize_md_links(
+        &self,
+        md: &str,
+        page_path: &PagePath,
+    ) -> String {
+        let mut edits: Vec<(Range<usize>, Option<String>)> = Vec::new();
+        let parser = Parser::new_ext(md, pcm::Options::all());
+        for (_, def) in parser.reference_definitions().iter() {
+            if let Some(url) = self.llms_link_url(&def.dest, page_path)
+                && let Some(pos) = md[def.span.clone()].find("]:")
+                && let Some(range) = link_dest_range(md, def.span.start + pos + 2)
+            {
+                edits.push((range, Some(url)));
+            }
+        }
+        // the links and images being parsed, with their new URL and
+        // where their text ends
+        let mut links: Vec<(Option<String>, usize)> = Vec::new();
+        for (event, range) in parser.into_offset_iter() {
+            match event {
+                Event::Start(Tag::MetadataBlock(_)) => {
+                    edits.push((range, None));
+                }
+                Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                    let url = self.llms_link_url(&dest_url, page_path);
+                    // the text starts after the `[` or `![`
+                    let text_start = range.start + md[range.clone()].find('[').unwrap_or(0) + 1;
+                    links.push((url, text_start));
+                }
+                Event::End(TagEnd::Link | TagEnd::Image) => {
+                    let Some((url, text_end)) = links.pop() else {
+                        continue;
+                    };
+                    if let Some((_, parent_text_end)) = links.last_mut() {
+                        *parent_text_end = range.end;
+                    }
+                    let Some(url) = url else {
+                        continue;
+                    };
+                    // for inline links, the destination follows the `](`
+                    // (reference links are handled with their definition)
+                    if md[text_end..range.end].starts_with("](")
+                        && let Some(range) = link_dest_range(md, text_end + 2)
+                    {
+                        edits.push((range, Some(url)));
+                    }
+                }
+                _ => {
+                    if let Some((_, text_end)) = links.last_mut() {
+                        *text_end = (*text_end).max(range.end);
+                    }
+                }
+            }
+        }
+        edits.sort_by_key(|(range, _)| range.start);
+        let mut rewritten = String::new();
+        let mut end = 0;
+        for (range, replacement) in edits {
+            if range.start < end {
+                continue; // eg a link in the front matter
+            }
+            rewritten.push_str(&md[end..range.start]);
+            if let Some(replacement) = replacement {
+                rewritten.push_str(&replacement);
+            }
+            end = range.end;
+        }
+        rewritten.push_str(&md[end..]);
+        rewritten
+    }
+
+    /// Return the absolute URL of a link or image of a page, if it
+    /// targets a page or image of the site, or the value of the variable
+    /// of a `--name` link
+    fn llms_link_url(
+        &self,
+        src: &str,
+        page_path: &PagePath,
+    ) -> Option<String> {
+        if let Some((_, path)) = self.img_src_path(src) {
+            return Some(self.llms_url(self.asset_path(&path)));
+        }
+        if let Some(var_name) = src.strip_prefix("--") {
+            return match self.expand_link_var(var_name, page_path)? {
+                LinkVar::Value(value) if value.starts_with("--") => Some(value),
+                LinkVar::Value(value) => {
+                    Some(self.llms_link_url(&value, page_path).unwrap_or(value))
+                }
+                LinkVar::Page(target) => Some(self.llms_url(&target.to_absolute_url(""))),
+            };
+        }
+        if src.starts_with('#') {
+            return Some(format!(
+                "{}{src}",
+                self.llms_url(&page_path.to_absolute_url(""))
+            ));
+        }
+        let target = self.internal_link_target(src, page_path)?;
+        if !self.pages.contains_key(&target) {
+            return None;
+        }
+        let url = self.llms_url(&target.to_absolute_url(""));
+        Some(match src.split_once('#') {
+            Some((_, hash)) if !hash.is_empty() => format!("{url}#{hash}"),
+            _ => url,
+        })
+    }
+}
+
+/// Return the range, in the Markdown, of the destination of a link
+/// starting at or after `start` (after the `](` of an inline link or
+/// the `]:` of a reference definition), without its `<>` if any
+fn link_dest_range(
+    md: &str,
+    start: usize,
+) -> Option<Range<usize>> {
+    let start = start + md[start..].len() - md[start..].trim_start().len();
+    let rest = &md[start..];
+    if let Some(rest) = rest.strip_prefix('<') {
+        let end = rest.find(['>', '\n'])?;
+        return Some(start + 1..start + 1 + end);
+    }
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(start..start + i),
+            ')' => depth -= 1,
+            _ if c.is_whitespace() => return Some(start..start + i),
+            _ => {}
+        }
+    }
+    Some(start..md.len())
+}
+
+#[test]
+fn test_llms_txt() {
+    let test_project = TestProject::new("llms");
+    test_project
+        .write(
+            "src/index.md",
+            "---\ndescription: The intro\n---\n# Intro\n\nSee [the setup](guide/setup.md#install).\n\n\
+            [escaped](guide/setup.md#in\\_stall) [angled](<guide/setup.md> \"title\") \
+            [next](--next) [repo](--repo)\n",
+        )
+        .write(
+            "src/guide/setup.md",
+            "# Install\n\n![logo](../img/logo.png)\n\n[Install][install]\n\n[install]: #install\n",
+        );
+    let project = test_project.load(
+        r#"{
+            title: Test
+            description: A test site
+            base-url: https://example.com/doc
+            llms-txt: true
+            vars: {
+                repo: https://example.com/repo
+            }
+            site-map: {
+                Home: index.md
+                Guide: {
+                    Setup: guide/setup.md
+                }
+            }
+        }"#,
+    );
+    project.build().unwrap();
+    assert_eq!(
+        test_project.output("llms.txt"),
+        "# Test\n\n> A test site\n\n## Pages\n\n- [Home](https://example.com/doc/index.md)\n\n\
+        ## Guide\n\n- [Setup](https://example.com/doc/guide/setup/index.md)\n",
+    );
+    let full = test_project.output("llms-full.txt");
+    assert!(!full.contains("The intro"));
+    assert!(full.contains("<!-- https://example.com/doc/guide/setup/ -->"));
+    assert!(full.contains("[the setup](https://example.com/doc/guide/setup/#install)"));
+    assert!(full.contains("[escaped](https://example.com/doc/guide/setup/#in_stall)"));
+    assert!(full.contains("[angled](<https://example.com/doc/guide/setup/> \"title\")"));
+    assert!(full.contains("[next](https://example.com/doc/guide/setup/)"));
+    assert!(full.contains("[repo](https://example.com/repo)"));
+    assert!(full.contains("![logo](https://example.com/doc/img/logo.png)"));
+    assert!(full.contains("[install]: https://example.com/doc/guide/setup/#install"));
+    assert_eq!(
+        test_project.output("guide/setup/index.md"),
+        fs::read_to_string(test_project.root.join("src/guide/setup.md")).unwrap(),
+    );
+}
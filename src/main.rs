@@ -1,3 +1,14 @@
-fn main() -> ddoc::DdResult<()> {
-    ddoc::run()
+use {
+    std::process::ExitCode,
+    termimad::crossterm::style::Stylize,
+};
+
+fn main() -> ExitCode {
+    match ddoc::run() {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("{} {e}", "Error:".red().bold());
+            ExitCode::FAILURE
+        }
+    }
 }
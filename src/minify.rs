@@ -0,0 +1,405 @@
+//! Conservative minification of the generated HTML and of the
+//! CSS and JS files, for production builds.
+//!
+//! The aim isn't to produce the smallest possible files but to remove what
+//! can be removed without any risk of changing the behavior or the rendering.
+
+/// Tags whose content must be kept as is
+const RAW_TAGS: &[&str] = &["pre", "textarea", "script", "style"];
+
+/// Tags around which whitespace can be removed without changing the rendering
+const BLOCK_TAGS: &[&str] = &[
+    "html",
+    "head",
+    "body",
+    "meta",
+    "link",
+    "title",
+    "base",
+    "script",
+    "style",
+    "div",
+    "p",
+    "ul",
+    "ol",
+    "li",
+    "dl",
+    "dt",
+    "dd",
+    "nav",
+    "header",
+    "footer",
+    "main",
+    "section",
+    "article",
+    "aside",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "pre",
+    "blockquote",
+    "figure",
+    "figcaption",
+    "table",
+    "thead",
+    "tbody",
+    "tfoot",
+    "tr",
+    "th",
+    "td",
+    "form",
+    "fieldset",
+    "details",
+    "summary",
+];
+
+/// Minify HTML: remove comments, collapse whitespace, and remove it around
+/// block tags, keeping untouched the content of `<pre>`, `<textarea>`
+/// and `<script>` elements (the content of `<style>` elements is minified as CSS)
+pub fn minify_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut pending_space = false;
+    let mut after_block_tag = true;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            pending_space = true;
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if let Some(comment) = rest.strip_prefix("<!--")
+            && !comment.starts_with('[')
+        {
+            rest = comment.find("-->").map_or("", |i| &comment[i + 3..]);
+            continue;
+        }
+        if c == '<' {
+            let tag = &rest[..tag_len(rest)];
+            let name = tag_name(tag);
+            let is_block = name.starts_with('!') || BLOCK_TAGS.contains(&name.as_str());
+            if pending_space && !after_block_tag && !is_block {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.push_str(tag);
+            rest = &rest[tag.len()..];
+            after_block_tag = is_block;
+            if !tag.starts_with("</") && RAW_TAGS.contains(&name.as_str()) {
+                let end = find_closing_tag(rest, &name).unwrap_or(rest.len());
+                if name == "style" {
+                    out.push_str(&minify_css(&rest[..end]));
+                } else {
+                    out.push_str(&rest[..end]);
+                }
+                rest = &rest[end..];
+            }
+            continue;
+        }
+        if pending_space && !after_block_tag {
+            out.push(' ');
+        }
+        pending_space = false;
+        after_block_tag = false;
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Return the length of the tag starting `s`, up to and including the
+/// closing `>` (which isn't searched in quoted attribute values)
+fn tag_len(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Return the position in `s` of the closing tag of the element whose
+/// lowercased name is given, the case of the tag being ignored
+fn find_closing_tag(
+    s: &str,
+    name: &str,
+) -> Option<usize> {
+    let closing = format!("</{name}");
+    s.as_bytes()
+        .windows(closing.len())
+        .position(|window| window.eq_ignore_ascii_case(closing.as_bytes()))
+}
+
+/// Return the lowercased name of a tag, eg `div` for `<div class=a>`
+/// or `</div>`, and `!doctype` for `<!DOCTYPE html>`
+fn tag_name(tag: &str) -> String {
+    let tag = tag.trim_start_matches('<').trim_start_matches('/');
+    tag.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '!' || *c == '-')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Minify CSS: remove comments, collapse whitespace, and remove it where
+/// it's not needed (around braces, semicolons, commas, child combinators)
+pub fn minify_css(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut pending_space = false;
+    while let Some(c) = chars.next() {
+        if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut previous = ' ';
+            for c in chars.by_ref() {
+                if previous == '*' && c == '/' {
+                    break;
+                }
+                previous = c;
+            }
+            pending_space = true;
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space
+            && !out.is_empty()
+            && !out.ends_with(['{', '}', ';', ',', '>', ':', '('])
+            && !matches!(c, '{' | '}' | ';' | ',' | '>' | ')')
+        {
+            out.push(' ');
+        }
+        pending_space = false;
+        if c == '}' && out.ends_with(';') {
+            out.pop();
+        }
+        out.push(c);
+        if c == '"' || c == '\'' {
+            copy_string(c, &mut chars, &mut out);
+        }
+    }
+    out
+}
+
+/// Minify JS, conservatively: remove comments, blank lines, and indentation,
+/// and collapse spaces, but keep the line breaks which may be significant
+pub fn minify_js(js: &str) -> String {
+    let mut out = String::with_capacity(js.len());
+    let mut chars = js.chars().peekable();
+    let mut pending_space = false;
+    // for each open brace, whether it starts a substitution in a template literal
+    let mut braces = Vec::new();
+    // for each open parenthesis, whether it follows a control keyword, as in `if (`
+    let mut parens = Vec::new();
+    let mut after_control_parens = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                let mut multiline = false;
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    multiline |= c == '\n';
+                    previous = c;
+                }
+                if multiline {
+                    push_js_newline(&mut out);
+                    pending_space = false;
+                } else {
+                    pending_space = true;
+                }
+            }
+            '\n' => {
+                push_js_newline(&mut out);
+                pending_space = false;
+            }
+            _ if c.is_whitespace() => {
+                pending_space = true;
+            }
+            _ => {
+                if pending_space && !out.is_empty() && !out.ends_with('\n') {
+                    out.push(' ');
+                }
+                pending_space = false;
+                let regex_allowed = c == '/' && js_regex_allowed(&out, after_control_parens);
+                if c == '(' {
+                    parens.push(ends_with_js_word(&out, &["if", "while", "for", "with"]));
+                }
+                out.push(c);
+                match c {
+                    '"' | '\'' => copy_string(c, &mut chars, &mut out),
+                    '`' if copy_template(&mut chars, &mut out) => braces.push(true),
+                    '{' => braces.push(false),
+                    '}' if braces.pop() == Some(true) && copy_template(&mut chars, &mut out) => {
+                        braces.push(true);
+                    }
+                    ')' => after_control_parens = parens.pop().unwrap_or(false),
+                    '/' if regex_allowed => copy_regex(&mut chars, &mut out),
+                    _ => {}
+                }
+            }
+        }
+    }
+    if out.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+fn push_js_newline(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Tell whether a `/` following `before` starts a regular expression
+/// literal rather than being a division, knowing whether the last closing
+/// parenthesis ends the condition of a control statement, as in `if (a) /b/`
+fn js_regex_allowed(
+    before: &str,
+    after_control_parens: bool,
+) -> bool {
+    let before = before.trim_end();
+    let Some(last) = before.chars().last() else {
+        return true;
+    };
+    if last == ')' {
+        return after_control_parens;
+    }
+    if "(,=:[!&|?{};+-*%<>~^\n".contains(last) {
+        return true;
+    }
+    ends_with_js_word(
+        before,
+        &[
+            "return", "typeof", "case", "do", "else", "new", "in", "of", "yield",
+        ],
+    )
+}
+
+/// Tell whether the JS code ends with one of the given words
+fn ends_with_js_word(
+    js: &str,
+    words: &[&str],
+) -> bool {
+    let js = js.trim_end();
+    let before_word =
+        js.trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    words.contains(&&js[before_word.len()..])
+}
+
+/// Copy the rest of a string literal, whose opening quote was already copied
+fn copy_string(
+    quote: char,
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    out: &mut String,
+) {
+    while let Some(c) = chars.next() {
+        out.push(c);
+        if c == '\\' {
+            if let Some(c) = chars.next() {
+                out.push(c);
+            }
+        } else if c == quote {
+            return;
+        }
+    }
+}
+
+/// Copy the rest of a template literal, whose opening backtick (or the `}`
+/// ending a substitution) was already copied, up to its end or up to the
+/// start of a substitution, in which case return true
+fn copy_template(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    out: &mut String,
+) -> bool {
+    while let Some(c) = chars.next() {
+        out.push(c);
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    out.push(c);
+                }
+            }
+            '`' => return false,
+            '$' if chars.next_if_eq(&'{').is_some() => {
+                out.push('{');
+                return true;
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Copy the rest of a regular expression literal, whose opening `/` was
+/// already copied (the flags are copied as normal chars)
+fn copy_regex(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    out: &mut String,
+) {
+    let mut in_class = false;
+    while let Some(c) = chars.next() {
+        out.push(c);
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    out.push(c);
+                }
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return,
+            '\n' => return, // not a regex, after all
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_minify_html() {
+    let html = "<!DOCTYPE html>\n<html>\n<head>\n  <title>T</title>\n</head>\n<body>\n<!-- c -->\n<p>Some  <em>text</em>\n  and\tmore</p>\n<pre>a\n  b</pre>\n<style>\na { color: red; }\n</style>\n</body>\n</html>\n";
+    assert_eq!(
+        minify_html(html),
+        "<!DOCTYPE html><html><head><title>T</title></head><body><p>Some <em>text</em> and more</p><pre>a\n  b</pre><style>a{color:red}</style></body></html>",
+    );
+    assert_eq!(
+        minify_html("<SCRIPT>a  =  1</SCRIPT>\n<p>x</p>"),
+        "<SCRIPT>a  =  1</SCRIPT><p>x</p>",
+    );
+}
+
+#[test]
+fn test_minify_css() {
+    let css = "/* comment */\n.a > .b,\n.c :hover {\n    color: red;\n    content: \"a  ;  b\";\n}\n@media (min-width: 5px) and (max-width: 9px) {\n  a { margin: 0 auto; }\n}\n";
+    assert_eq!(
+        minify_css(css),
+        r#".a>.b,.c :hover{color:red;content:"a  ;  b"}@media (min-width:5px) and (max-width:9px){a{margin:0 auto}}"#,
+    );
+}
+
+#[test]
+fn test_minify_js() {
+    let js = "// comment\nfunction f(a, b) {\n    /* block */ let s = \"x // y\";\n\n    let r = /a\\/b[/]/g;\n    return a / b; // division\n}\nlet t = `a\n  b`;\n";
+    assert_eq!(
+        minify_js(js),
+        "function f(a, b) {\nlet s = \"x // y\";\nlet r = /a\\/b[/]/g;\nreturn a / b;\n}\nlet t = `a\n  b`;",
+    );
+    let js = "if (a) /x\\//.test(s) && f(1) / 2;\nlet u = `a ${ b ? `c ${d}` : \"}\" }  //e`; // comment\n";
+    assert_eq!(
+        minify_js(js),
+        "if (a) /x\\//.test(s) && f(1) / 2;\nlet u = `a ${ b ? `c ${d}` : \"}\" }  //e`;",
+    );
+}
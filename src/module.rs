@@ -23,14 +23,21 @@ impl Module {
         root: &Path,
     ) -> DdResult<Self> {
         let config = Config::in_dir(root)?;
-        let src_path = root.join("src");
-        let module = Self {
+        Ok(Self::new(name, root, config))
+    }
+    /// Make a module from the given root directory, with an already known
+    /// config (or none), without looking for a config file
+    pub fn new<S: Into<String>>(
+        name: S,
+        root: &Path,
+        config: Option<Sourced<Config>>,
+    ) -> Self {
+        Self {
             name: name.into(),
             config,
             root: root.to_owned(),
-            src_path,
-        };
-        Ok(module)
+            src_path: root.join("src"),
+        }
     }
 
     pub fn is_main(&self) -> bool {
@@ -42,6 +49,7 @@ impl Module {
         subdir: &str,
         ext_filter: Option<&str>,
         entries: &mut Vec<StaticEntry>,
+        diagnostics: &mut Vec<Diagnostic>,
     ) -> DdResult<()> {
         let static_src = self.src_path.join(subdir);
         let prefix = format!("{}/", subdir);
@@ -50,54 +58,47 @@ impl Module {
         //} else {
         //    format!("{}/{}/", subdir, self.name)
         //};
-        StaticEntry::list_in(&static_src, &prefix, ext_filter, entries)
+        StaticEntry::list_in(&static_src, &prefix, ext_filter, entries, diagnostics)
     }
 
     pub fn list_js(
         &self,
         entries: &mut Vec<StaticEntry>,
+        diagnostics: &mut Vec<Diagnostic>,
     ) -> DdResult<()> {
-        self.list_static_entries_in("js", Some(".js"), entries)
+        self.list_static_entries_in("js", Some(".js"), entries, diagnostics)
     }
     pub fn list_css(
         &self,
         entries: &mut Vec<StaticEntry>,
+        diagnostics: &mut Vec<Diagnostic>,
     ) -> DdResult<()> {
-        self.list_static_entries_in("css", Some(".css"), entries)
+        self.list_static_entries_in("css", Some(".css"), entries, diagnostics)
     }
 
     pub fn add_watch_targets(
         &self,
         targets: &mut Vec<WatchTarget>,
     ) {
-        if let Some(config) = &self.config {
-            let config_path = config.src();
-            targets.push(WatchTarget::new_file(config_path));
-        }
+        // the root is watched, rather than the config file, so that
+        // a config file created or renamed to another format is seen
+        targets.push(WatchTarget::new_config_dir(&self.root));
         if self.src_path.exists() {
             targets.push(WatchTarget::new_dir(&self.src_path));
         }
     }
-    fn copy_static_into(
+    /// List the static files (js, css, and img) to copy in the build directory,
+    /// with their path relative to the build root and their path on disk
+    pub fn list_static_files(
         &self,
-        dir: &str,
-        build_root: &Path, // build root without the name
+        files: &mut Vec<(String, PathBuf)>,
     ) -> DdResult<()> {
-        let static_src = self.src_path.join(dir);
-        if !static_src.exists() {
-            return Ok(());
+        for dir in ["js", "css", "img"] {
+            let static_src = self.src_path.join(dir);
+            if static_src.exists() {
+                list_normal_files_recursive(&static_src, &format!("{dir}/"), files)?;
+            }
         }
-        let static_dst = build_root.join(dir); //.join(&self.name);
-        copy_normal_recursive(&static_src, &static_dst)?;
-        Ok(())
-    }
-    pub fn copy_all_statics_into(
-        &self,
-        build_root: &Path, // build root without the name
-    ) -> DdResult<()> {
-        self.copy_static_into("js", build_root)?;
-        self.copy_static_into("css", build_root)?;
-        self.copy_static_into("img", build_root)?;
         Ok(())
     }
 }
@@ -0,0 +1,161 @@
+use {
+    crate::*,
+    rustc_hash::FxHashMap,
+};
+
+/// The navigation structure of the site, computed once from the site map
+/// when the project is loaded: order of the pages, sections, parents, etc.
+#[derive(Debug, Default, Clone)]
+pub struct NavIndex {
+    /// The paths of the pages, in the order of the site map
+    order: Vec<PagePath>,
+    entries: FxHashMap<PagePath, NavEntry>,
+    /// The entries of the site map listing an already listed page,
+    /// as (title, page path)
+    duplicates: Vec<(String, PagePath)>,
+}
+
+/// The position of a page in the site map
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavEntry {
+    /// Index of the page in the order of the site map
+    pub index: usize,
+    /// Title of the page in the site map
+    pub title: String,
+    /// Titles of the sub menus containing the page, from the root of the
+    /// site map, which is the path to its section
+    pub parents: Vec<String>,
+}
+
+impl NavIndex {
+    /// Build the index of the site map. When a page is listed several
+    /// times, only its first occurrence is considered.
+    pub fn new(site_map: &PageList) -> Self {
+        let mut index = Self::default();
+        index.add_list(site_map, &mut Vec::new());
+        index
+    }
+    fn add_list(
+        &mut self,
+        list: &PageList,
+        parents: &mut Vec<String>,
+    ) {
+        for (title, item) in &list.items {
+            match item {
+                ListItem::Page(page_path) => {
+                    if self.entries.contains_key(page_path) {
+                        self.duplicates.push((title.clone(), page_path.clone()));
+                        continue;
+                    }
+                    self.entries.insert(
+                        page_path.clone(),
+                        NavEntry {
+                            index: self.order.len(),
+                            title: title.clone(),
+                            parents: parents.clone(),
+                        },
+                    );
+                    self.order.push(page_path.clone());
+                }
+                ListItem::List(submenu) => {
+                    parents.push(title.clone());
+                    self.add_list(submenu, parents);
+                    parents.pop();
+                }
+            }
+        }
+    }
+    /// The paths of the pages, in the order of the site map
+    pub fn order(&self) -> &[PagePath] {
+        &self.order
+    }
+    /// The entries of the site map which were ignored because their
+    /// page was already listed, as (title, page path)
+    pub fn duplicates(&self) -> &[(String, PagePath)] {
+        &self.duplicates
+    }
+    pub fn get(
+        &self,
+        page_path: &PagePath,
+    ) -> Option<&NavEntry> {
+        self.entries.get(page_path)
+    }
+    /// Index of the page in the order of the site map
+    pub fn index_of(
+        &self,
+        page_path: &PagePath,
+    ) -> Option<usize> {
+        self.get(page_path).map(|entry| entry.index)
+    }
+    pub fn previous(
+        &self,
+        page_path: &PagePath,
+    ) -> Option<&PagePath> {
+        let index = self.index_of(page_path)?;
+        index.checked_sub(1).map(|index| &self.order[index])
+    }
+    pub fn next(
+        &self,
+        page_path: &PagePath,
+    ) -> Option<&PagePath> {
+        let index = self.index_of(page_path)?;
+        self.order.get(index + 1)
+    }
+    /// Titles of the sub menus containing the page, from the root of the site map
+    pub fn parents(
+        &self,
+        page_path: &PagePath,
+    ) -> &[String] {
+        self.get(page_path)
+            .map(|entry| entry.parents.as_slice())
+            .unwrap_or_default()
+    }
+    /// The pages listed in the same section (the site map or a sub menu)
+    /// as the given page, not counting the ones of sub menus
+    pub fn section_pages(
+        &self,
+        page_path: &PagePath,
+    ) -> impl Iterator<Item = &PagePath> {
+        let parents = self.parents(page_path);
+        self.order
+            .iter()
+            .filter(move |p| self.entries.get(*p).is_some_and(|e| e.parents == parents))
+    }
+}
+
+#[test]
+fn test_nav_index() {
+    let site_map: PageList = deser_hjson::from_str(
+        r#"
+        Home: index.md
+        Guide: {
+            Intro: guide/intro.md
+            Deep: {
+                Inner: guide/inner.md
+            }
+            Setup: guide/setup.md
+        }
+        Again: guide/intro.md
+        Last: last.md
+        "#,
+    )
+    .unwrap();
+    let nav = NavIndex::new(&site_map);
+    let path = |s: &str| s.parse::<PagePath>().unwrap();
+    assert_eq!(nav.order().len(), 5);
+    assert_eq!(
+        nav.duplicates(),
+        [("Again".to_string(), path("guide/intro"))]
+    );
+    assert_eq!(nav.index_of(&path("last")), Some(4));
+    assert_eq!(nav.previous(&path("index")), None);
+    assert_eq!(nav.next(&path("guide/setup")), Some(&path("last")));
+    assert_eq!(
+        nav.previous(&path("guide/setup")),
+        Some(&path("guide/inner"))
+    );
+    assert_eq!(nav.parents(&path("guide/inner")), ["Guide", "Deep"]);
+    assert_eq!(nav.get(&path("guide/intro")).unwrap().title, "Intro");
+    let section: Vec<_> = nav.section_pages(&path("guide/setup")).collect();
+    assert_eq!(section, [&path("guide/intro"), &path("guide/setup")]);
+}
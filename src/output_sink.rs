@@ -0,0 +1,237 @@
+use {
+    crate::*,
+    std::{
+        collections::BTreeMap,
+        fs,
+        path::{
+            Path,
+            PathBuf,
+        },
+        sync::{
+            Arc,
+            Mutex,
+            MutexGuard,
+        },
+    },
+};
+
+/// Where the files of a built site go
+///
+/// Paths are relative to the root of the site, with `/` as separator,
+/// eg `css/main.css` or `guide/index.html`.
+///
+/// Files are written from several threads, hence the `Send + Sync` bound.
+pub trait OutputSink: Send + Sync {
+    /// Write a file, replacing the previous one if any
+    fn write(
+        &self,
+        path: &str,
+        content: &[u8],
+    ) -> DdResult<()>;
+    /// Copy a file from the disk
+    fn copy(
+        &self,
+        path: &str,
+        src_path: &Path,
+    ) -> DdResult<()> {
+        self.write(path, &fs::read(src_path)?)
+    }
+    /// Read a file previously written, if it exists
+    fn read(
+        &self,
+        path: &str,
+    ) -> DdResult<Option<Vec<u8>>>;
+    fn exists(
+        &self,
+        path: &str,
+    ) -> bool;
+    /// Remove a file, if it exists
+    fn remove(
+        &self,
+        path: &str,
+    ) -> DdResult<()>;
+}
+
+/// Let a sink be shared, so that its content can be read after the build
+impl<S: OutputSink + ?Sized> OutputSink for Arc<S> {
+    fn write(
+        &self,
+        path: &str,
+        content: &[u8],
+    ) -> DdResult<()> {
+        (**self).write(path, content)
+    }
+    fn copy(
+        &self,
+        path: &str,
+        src_path: &Path,
+    ) -> DdResult<()> {
+        (**self).copy(path, src_path)
+    }
+    fn read(
+        &self,
+        path: &str,
+    ) -> DdResult<Option<Vec<u8>>> {
+        (**self).read(path)
+    }
+    fn exists(
+        &self,
+        path: &str,
+    ) -> bool {
+        (**self).exists(path)
+    }
+    fn remove(
+        &self,
+        path: &str,
+    ) -> DdResult<()> {
+        (**self).remove(path)
+    }
+}
+
+/// The default sink, writing the files in a directory
+#[derive(Debug, Default, Clone)]
+pub struct FsSink {
+    pub root: PathBuf,
+}
+
+impl FsSink {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+    fn create_parent_dir(
+        &self,
+        path: &Path,
+    ) -> DdResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(())
+    }
+}
+
+impl OutputSink for FsSink {
+    fn write(
+        &self,
+        path: &str,
+        content: &[u8],
+    ) -> DdResult<()> {
+        let dst_path = self.root.join(path);
+        self.create_parent_dir(&dst_path)?;
+        fs::write(&dst_path, content)?;
+        Ok(())
+    }
+    fn copy(
+        &self,
+        path: &str,
+        src_path: &Path,
+    ) -> DdResult<()> {
+        let dst_path = self.root.join(path);
+        self.create_parent_dir(&dst_path)?;
+        if dst_path.exists() {
+            fs::remove_file(&dst_path)?; // to have it updated
+        }
+        fs::copy(src_path, &dst_path)?;
+        Ok(())
+    }
+    fn read(
+        &self,
+        path: &str,
+    ) -> DdResult<Option<Vec<u8>>> {
+        let file_path = self.root.join(path);
+        if !file_path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read(file_path)?))
+    }
+    fn exists(
+        &self,
+        path: &str,
+    ) -> bool {
+        self.root.join(path).exists()
+    }
+    /// Remove the file, then the directories left empty
+    fn remove(
+        &self,
+        path: &str,
+    ) -> DdResult<()> {
+        let file_path = self.root.join(path);
+        if file_path.exists() {
+            fs::remove_file(&file_path)?;
+        }
+        let mut dir = file_path.parent();
+        while let Some(d) = dir {
+            if d == self.root || fs::remove_dir(d).is_err() {
+                break;
+            }
+            dir = d.parent();
+        }
+        Ok(())
+    }
+}
+
+/// A sink keeping the files in memory, for tests or post-processing
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    files: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemorySink {
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, Vec<u8>>> {
+        self.files
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+    /// Return the content of a file
+    pub fn get(
+        &self,
+        path: &str,
+    ) -> Option<Vec<u8>> {
+        self.lock().get(path).cloned()
+    }
+    /// Return the content of a file, if it's valid UTF-8
+    pub fn get_string(
+        &self,
+        path: &str,
+    ) -> Option<String> {
+        self.get(path)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    }
+    /// Return the paths of all files, sorted
+    pub fn paths(&self) -> Vec<String> {
+        self.lock().keys().cloned().collect()
+    }
+    /// Return all the files, keyed by their paths
+    pub fn files(&self) -> BTreeMap<String, Vec<u8>> {
+        self.lock().clone()
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn write(
+        &self,
+        path: &str,
+        content: &[u8],
+    ) -> DdResult<()> {
+        self.lock().insert(path.to_string(), content.to_vec());
+        Ok(())
+    }
+    fn read(
+        &self,
+        path: &str,
+    ) -> DdResult<Option<Vec<u8>>> {
+        Ok(self.get(path))
+    }
+    fn exists(
+        &self,
+        path: &str,
+    ) -> bool {
+        self.lock().contains_key(path)
+    }
+    fn remove(
+        &self,
+        path: &str,
+    ) -> DdResult<()> {
+        self.lock().remove(path);
+        Ok(())
+    }
+}
@@ -1,16 +1,28 @@
 use {
     crate::*,
+    pulldown_cmark::{
+        self as pcm,
+        Event,
+        Parser,
+        Tag,
+        TagEnd,
+    },
     std::{
         fs,
-        path::PathBuf,
+        path::{
+            Path,
+            PathBuf,
+        },
     },
-    termimad::crossterm::style::Stylize,
 };
 
 pub struct Page {
     pub title: String,
     pub page_path: PagePath,
     pub md_file_path: PathBuf,
+    /// The description of the page, taken from its front matter
+    /// or else from its first paragraph, read when the page is loaded
+    pub description: Option<String>,
 }
 
 impl Page {
@@ -19,13 +31,40 @@ impl Page {
         page_path: PagePath,
         md_file_path: PathBuf,
     ) -> Self {
+        let description = Self::read_description(&md_file_path);
         Self {
             title,
             page_path,
             md_file_path,
+            description,
+        }
+    }
+
+    /// Make a page which isn't written from a Markdown file of its own,
+    /// like the print page
+    pub fn generated(
+        title: String,
+        page_path: PagePath,
+    ) -> Self {
+        Self {
+            title,
+            page_path,
+            md_file_path: PathBuf::new(),
+            description: None,
         }
     }
 
+    /// Read again the description of the page, after a change of its
+    /// Markdown file, and tell whether it changed
+    pub fn reload_description(&mut self) -> bool {
+        let description = Self::read_description(&self.md_file_path);
+        if description == self.description {
+            return false;
+        }
+        self.description = description;
+        true
+    }
+
     /// Write the full HTML for this page into the given `html` String
     ///
     /// # Errors
@@ -35,15 +74,100 @@ impl Page {
         html: &mut String,
         project: &Project,
     ) -> DdResult<()> {
+        match self.page_writer(project)? {
+            Some(page_writer) => page_writer.write_html(html),
+            None => Ok(()),
+        }
+    }
+
+    /// Read the Markdown of the page and render it, ready to be written
+    ///
+    /// Return `None` (and report the problem) if the file can't be read.
+    pub fn page_writer<'p>(
+        &'p self,
+        project: &'p Project,
+    ) -> DdResult<Option<PageWriter<'p>>> {
         let Ok(md) = fs::read_to_string(&self.md_file_path) else {
-            eprintln!(
-                "{} {} could not be read, skipping.",
-                "ERROR:".red().bold(),
-                self.md_file_path.to_string_lossy().yellow()
+            project.report(
+                &self.page_path,
+                Diagnostic::error("page file could not be read")
+                    .at(Location::new(&self.md_file_path)),
             );
-            return Ok(());
+            return Ok(None);
         };
-        let page_writer = PageWriter::new(self, project, &md)?;
-        page_writer.write_html(html)
+        PageWriter::new(self, project, &md).map(Some)
     }
+
+    /// Read the description of a page, taken from the front matter of
+    /// its Markdown file or else from its first paragraph
+    fn read_description(md_file_path: &Path) -> Option<String> {
+        let md = fs::read_to_string(md_file_path).ok()?;
+        if let Some(description) = FrontMatter::parse(&md).description() {
+            return Some(description.to_string());
+        }
+        let mut paragraph: Option<String> = None;
+        for event in Parser::new_ext(&md, pcm::Options::all()) {
+            match (event, &mut paragraph) {
+                (Event::Start(Tag::Paragraph), None) => {
+                    paragraph = Some(String::new());
+                }
+                (Event::Text(text) | Event::Code(text), Some(paragraph)) => {
+                    paragraph.push_str(&text);
+                }
+                (Event::SoftBreak | Event::HardBreak, Some(paragraph)) => {
+                    paragraph.push(' ');
+                }
+                (Event::End(TagEnd::Paragraph), Some(_)) => {
+                    break;
+                }
+                _ => {}
+            }
+        }
+        paragraph.filter(|p| !p.trim().is_empty())
+    }
+}
+
+#[test]
+fn test_child_page_cards() {
+    let test_project = TestProject::new("cards");
+    test_project
+        .write("src/index.md", "# Home\n")
+        .write("src/guide/setup.md", "# Setup\n\nHow to install.\n")
+        .write("src/guide/usage.md", "# Usage\n");
+    let project = test_project.load(
+        r#"{
+            title: Test
+            site-map: {
+                Home: index.md
+                Guide: {
+                    Setup: guide/setup.md
+                    Usage: guide/usage.md
+                }
+            }
+            body: {
+                details: {
+                    open: true
+                    hidden: false
+                    text: more
+                }
+                ddoc-page-list: {
+                    depth: 2
+                    layout: cards
+                }
+            }
+        }"#,
+    );
+    assert_eq!(
+        project.pages[&"guide/setup".parse().unwrap()]
+            .description
+            .as_deref(),
+        Some("How to install."),
+    );
+    project.build().unwrap();
+    let html = test_project.output("index.html");
+    assert!(html.contains("<details open>\nmore</details>"));
+    assert!(html.contains(
+        "</a>\n<div class=\"page-cards\">\n<a class=\"page-card\" href=\"guide/setup/\">"
+    ));
+    assert!(html.contains("<span class=\"page-description\">How to install.</span>"));
 }
@@ -1,7 +1,14 @@
 use {
     crate::*,
     lazy_regex::regex_captures,
+    schemars::{
+        JsonSchema,
+        Schema,
+        SchemaGenerator,
+        json_schema,
+    },
     std::{
+        borrow::Cow,
         fmt,
         path::{
             Path,
@@ -203,3 +210,15 @@ impl<'de> serde::Deserialize<'de> for PagePath {
         s.parse().map_err(serde::de::Error::custom)
     }
 }
+impl JsonSchema for PagePath {
+    fn schema_name() -> Cow<'static, str> {
+        "PagePath".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Path to a markdown file, relative to the src directory",
+            "type": "string",
+            "pattern": r"^([\w-]+/)*[\w-]+(\.md)?$",
+        })
+    }
+}
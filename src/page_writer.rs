@@ -1,27 +1,55 @@
 use {
     crate::*,
-    lazy_regex::regex_remove,
+    lazy_regex::{
+        regex_remove,
+        regex_replace_all,
+    },
     pulldown_cmark::{
         self as pcm,
         CowStr,
         Event,
+        HeadingLevel,
         Parser,
         Tag,
         TagEnd,
         html::push_html,
     },
     rustc_hash::FxHashMap,
-    std::fmt::Write,
-    termimad::crossterm::style::Stylize,
+    std::{
+        cell::RefCell,
+        fmt::Write,
+        fs,
+    },
 };
 
 pub struct PageWriter<'p> {
     page: &'p Page,
     project: &'p Project,
+    front_matter: FrontMatter,
     /// What goes inside the `<ul class=toc-content>` tag
     toc: String,
     /// What goes inside the `<main>` tag
     main: String,
+    heading_count: usize,
+    /// Targets of the links of the Markdown, as written in the HTML
+    links: Vec<String>,
+    search_sections: Vec<SearchSection>,
+    /// The IDs given to the headings of the page, and of the included
+    /// files, for them to be unique
+    id_counts: RefCell<FxHashMap<String, usize>>,
+}
+
+/// The HTML rendering of some Markdown, with what was found while rendering it
+#[derive(Debug, Default)]
+pub struct RenderedMarkdown {
+    pub html: String,
+    /// The LIs of the nav.page-toc
+    pub toc: String,
+    pub heading_count: usize,
+    /// Targets of the links, after rewriting
+    pub links: Vec<String>,
+    /// The text, split at headings, for the search index
+    pub search_sections: Vec<SearchSection>,
 }
 
 impl<'p> PageWriter<'p> {
@@ -31,85 +59,17 @@ impl<'p> PageWriter<'p> {
         md: &str,
     ) -> DdResult<Self> {
         let mut id_counts = FxHashMap::default();
-        let mut toc = String::new(); // stores the LIs of the nav.page-toc
-        let mut main = String::new(); // stores the HTML of the <main> tag
-
-        let mut events = Parser::new_ext(md, pcm::Options::all()).collect::<Vec<_>>();
-        for i in 0..events.len() {
-            match &mut events[i] {
-                // Rewrite the image source
-                Event::Start(Tag::Image { dest_url, .. }) => {
-                    *dest_url = CowStr::from(project.img_url(dest_url, &page.page_path));
-                }
-
-                // rewrite internal links
-                Event::Start(Tag::Link { dest_url, .. }) => {
-                    if let Some(new_url) = project.rewrite_link_url(dest_url, &page.page_path) {
-                        *dest_url = CowStr::from(new_url);
-                    }
-                }
-
-                _ => {}
-            }
-
-            // Generate IDs for headings if missing and
-            // generate the TOC's content
-            if let Event::Start(Tag::Heading { level, id, .. }) = &events[i] {
-                if id.is_none() {
-                    // Generate an ID from the heading text
-                    let mut heading_text = String::new();
-                    let mut j = i + 1;
-                    while j < events.len() {
-                        match &events[j] {
-                            Event::Code(code) => {
-                                if !heading_text.is_empty() {
-                                    heading_text.push(' ');
-                                }
-                                heading_text.push_str(code);
-                            }
-                            Event::Text(text) => {
-                                if !heading_text.is_empty() {
-                                    heading_text.push(' ');
-                                }
-                                heading_text.push_str(text);
-                            }
-                            Event::End(TagEnd::Heading(_)) => {
-                                break;
-                            }
-                            _ => {}
-                        }
-                        j += 1;
-                    }
-                    let mut new_id = heading_text
-                        .to_lowercase()
-                        .chars()
-                        .filter(|c| c.is_alphanumeric() || *c == ' ')
-                        .map(|c| if c == ' ' { '-' } else { c })
-                        .collect::<String>();
-                    let count = id_counts.entry(new_id.clone()).or_insert(0);
-                    *count += 1;
-                    if *count > 1 {
-                        new_id = format!("{}-{}", new_id, count);
-                    }
-                    writeln!(
-                        toc,
-                        "<li class=\"toc-item {level}\"><a href=#{new_id}>{heading_text}</a></li>"
-                    )?;
-                    if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
-                        *id = Some(CowStr::from(new_id));
-                    } else {
-                        unreachable!();
-                    }
-                }
-            }
-        }
-
-        push_html(&mut main, events.into_iter());
+        let rendered = render_markdown(md, project, &page.page_path, &mut id_counts)?;
         Ok(Self {
             page,
             project,
-            toc,
-            main,
+            id_counts: RefCell::new(id_counts),
+            front_matter: FrontMatter::parse(md),
+            toc: rendered.toc,
+            main: rendered.html,
+            heading_count: rendered.heading_count,
+            links: rendered.links,
+            search_sections: rendered.search_sections,
         })
     }
 
@@ -119,6 +79,34 @@ impl<'p> PageWriter<'p> {
     pub fn config(&self) -> &Config {
         &self.project.config
     }
+    /// Number of headings in the Markdown of the page
+    pub fn heading_count(&self) -> usize {
+        self.heading_count
+    }
+    /// Targets of the links in the Markdown of the page
+    pub fn links(&self) -> &[String] {
+        &self.links
+    }
+    /// The text of the Markdown of the page, split at headings
+    pub fn search_sections(&self) -> &[SearchSection] {
+        &self.search_sections
+    }
+    /// Record a problem found while rendering this page
+    fn report(
+        &self,
+        diagnostic: Diagnostic,
+    ) {
+        self.project.report(self.page_path(), diagnostic);
+    }
+    /// Record a problem about a value of the page or of the configs
+    fn report_about(
+        &self,
+        value: &str,
+        diagnostic: Diagnostic,
+    ) {
+        self.project
+            .report_about(self.page_path(), value, diagnostic);
+    }
 
     /// Write the full HTML for this page into the given `html` String
     ///
@@ -147,29 +135,86 @@ impl<'p> PageWriter<'p> {
         &self,
         html: &mut String,
     ) -> DdResult<()> {
-        html.push_str(HTML_START);
-        let title = format!("{} - {}", &self.page.title, &self.config().title());
+        self.write_html_head_with(html, "")
+    }
+
+    /// Write the `<head>`, as `write_html_head`, with some content added
+    /// at its end, eg a `<style>` element
+    ///
+    /// # Errors
+    /// Return `DdError` variants on write errors, not on project config/data errors
+    pub fn write_html_head_with(
+        &self,
+        html: &mut String,
+        extra_head: &str,
+    ) -> DdResult<()> {
+        writeln!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"{}\">",
+            escape_attr(self.config().language()),
+        )?;
+        html.push_str(HTML_HEAD_START);
+        let title = self.config().page_title(&self.page.title);
         writeln!(html, "<title>{}</title>", escape_text(&title))?;
+        let title = escape_attr(&title);
+        writeln!(html, r#"<meta property="og:title" content="{title}">"#)?;
+        writeln!(html, r#"<meta name="twitter:title" content="{title}">"#)?;
+        writeln!(html, r#"<meta property="og:type" content="website">"#)?;
         writeln!(
             html,
-            "<meta name=\"og-title\" content=\"{}\">",
-            escape_attr(&title)
+            r#"<meta property="og:site_name" content="{}">"#,
+            escape_attr(self.config().title()),
         )?;
-        if let Some(description) = self.config().description() {
+        let description = self
+            .front_matter
+            .description()
+            .or_else(|| self.config().description());
+        if let Some(description) = description {
             let description = escape_attr(description);
             writeln!(html, r#"<meta name="description" content="{description}">"#)?;
             writeln!(
                 html,
-                r#"<meta name="og-description" content="{description}">"#
+                r#"<meta property="og:description" content="{description}">"#
+            )?;
+            writeln!(
+                html,
+                r#"<meta name="twitter:description" content="{description}">"#
             )?;
         }
+        if let Some(base_url) = self.config().base_url() {
+            let url = escape_attr(&self.page_path().to_absolute_url(base_url));
+            writeln!(html, r#"<link rel="canonical" href="{url}">"#)?;
+            writeln!(html, r#"<meta property="og:url" content="{url}">"#)?;
+        }
+        let image = self
+            .front_matter
+            .image()
+            .or_else(|| self.config().image())
+            .map(|image| self.project.asset_path(image))
+            .and_then(|image| self.config().absolute_url(image));
+        if let Some(image) = &image {
+            let image = escape_attr(image);
+            writeln!(html, r#"<meta property="og:image" content="{image}">"#)?;
+            writeln!(html, r#"<meta name="twitter:image" content="{image}">"#)?;
+        }
+        let card = if image.is_some() {
+            "summary_large_image"
+        } else {
+            "summary"
+        };
+        writeln!(html, r#"<meta name="twitter:card" content="{card}">"#)?;
         if let Some(url) = self.config().favicon() {
             let url = self.project.img_url(url, self.page_path());
             writeln!(html, r#"<link rel="shortcut icon" href="{url}">"#)?;
         }
-        for e in self.project.list_js()? {
-            let url = self.project.static_url(&e.served_path, self.page_path());
-            writeln!(html, r#"<script src="{}?m={}"></script>"#, url, e.mtime)?;
+        if let Some(bundle) = self.project.js_bundle() {
+            let url = self.static_asset_url(bundle.path, bundle.mtime);
+            writeln!(html, r#"<script src="{url}"></script>"#)?;
+        } else {
+            for e in self.project.list_js() {
+                let url = self.static_asset_url(&e.served_path, e.mtime);
+                writeln!(html, r#"<script src="{url}"></script>"#)?;
+            }
         }
         before_0_16::write_special_js_headers_if_needed(
             self.page_path(),
@@ -177,24 +222,60 @@ impl<'p> PageWriter<'p> {
             self.project,
             html,
         )?;
-        for e in self.project.list_css()?.into_iter().rev() {
-            let url = self.project.static_url(&e.served_path, self.page_path());
-            writeln!(
-                html,
-                r#"<link href="{}?m={}" rel=stylesheet>"#,
-                url, e.mtime
-            )?;
+        if let Some(bundle) = self.project.css_bundle() {
+            let url = self.static_asset_url(bundle.path, bundle.mtime);
+            writeln!(html, r#"<link href="{url}" rel=stylesheet>"#)?;
+        } else {
+            for e in self.project.list_css().iter().rev() {
+                let url = self.static_asset_url(&e.served_path, e.mtime);
+                writeln!(html, r#"<link href="{url}" rel=stylesheet>"#)?;
+            }
         }
+        html.push_str(extra_head);
         html.push_str("</head>\n");
         Ok(())
     }
 
-    fn write_page_title(
+    /// Return the URL of a JS or CSS file, which is either fingerprinted
+    /// or has its modification time as query for cache busting
+    fn static_asset_url(
         &self,
-        html: &mut String,
-        page: &Page,
-    ) {
-        html.push_str(&escape_text(&page.title));
+        served_path: &str,
+        mtime: u64,
+    ) -> String {
+        let path = self.project.asset_path(served_path);
+        let url = self.project.static_url(path, self.page_path());
+        if path == served_path {
+            format!("{url}?m={mtime}")
+        } else {
+            url
+        }
+    }
+
+    /// Return the unescaped value of a `Text` item, which can be a string or
+    /// a dynamic value like the current page title
+    ///
+    /// Return `None` in case of missing expansion
+    fn text_value(
+        &self,
+        text: &Text,
+    ) -> Option<String> {
+        let value = match text {
+            Text::String(s) => s.clone(),
+            Text::PreviousPageTitle => self
+                .project
+                .previous_page(self.page_path())
+                .map(|page| page.title.clone())
+                .unwrap_or_default(),
+            Text::CurrentPageTitle => self.page.title.clone(),
+            Text::NextPageTitle => self
+                .project
+                .next_page(self.page_path())
+                .map(|page| page.title.clone())
+                .unwrap_or_default(),
+            Text::Var(var_name) => self.config().var(var_name)?,
+        };
+        Some(value)
     }
 
     /// Write the text for a `Text` item, which can be a string or a dynamic
@@ -207,40 +288,88 @@ impl<'p> PageWriter<'p> {
         html: &mut String,
         text: &Text,
     ) -> bool {
+        let Some(value) = self.text_value(text) else {
+            return false;
+        };
         match text {
-            Text::String(s) => html.push_str(&escape_text(s)),
-            Text::PreviousPageTitle => {
-                if let Some(prev_page) = self.project.previous_page(self.page_path()) {
-                    self.write_page_title(html, prev_page)
+            // vars are written as is, they may contain HTML
+            Text::Var(_) => html.push_str(&value),
+            _ => html.push_str(&escape_text(&value)),
+        }
+        true
+    }
+
+    /// Compute the value of an attribute of an HTML element, applying the same
+    /// expansions as for `ddoc-link`: `href` values are rewritten as links, `src`
+    /// values as image sources, and other values may be `--` prefixed text
+    /// expansions.
+    ///
+    /// Return `None` in case of missing expansion
+    fn attribute_value(
+        &self,
+        name: &str,
+        value: &str,
+    ) -> Option<String> {
+        let value = match name {
+            "href" => self.project.link_url(value, self.page_path()).into_owned(),
+            "src" => self.project.img_url(value, self.page_path()),
+            _ => return self.text_value(&Text::from(value)),
+        };
+        if value.starts_with("--") {
+            return None;
+        }
+        Some(value)
+    }
+
+    /// Write the string attributes of an HTML element, with their values expanded
+    ///
+    /// Return `false` in case of missing expansion (the element should probably
+    /// not be rendered)
+    fn write_attributes(
+        &self,
+        html: &mut String,
+        attributes: &Attributes,
+    ) -> DdResult<bool> {
+        for (name, value) in attributes {
+            let value = match value {
+                AttributeValue::String(value) => {
+                    let Some(value) = self.attribute_value(name, value) else {
+                        return Ok(false);
+                    };
+                    value
                 }
-            }
-            Text::CurrentPageTitle => self.write_page_title(html, self.page),
-            Text::NextPageTitle => {
-                if let Some(next_page) = self.project.next_page(self.page_path()) {
-                    self.write_page_title(html, next_page)
+                AttributeValue::Number(_) => value.to_string(),
+                // a boolean HTML attribute, like `hidden`, is present or absent
+                AttributeValue::Bool(true) => {
+                    write!(html, " {name}")?;
+                    continue;
                 }
-            }
-            Text::Var(var_name) => {
-                let Some(var_value) = self.config().var(var_name) else {
-                    return false;
-                };
-                html.push_str(var_value.as_str());
-            }
+                AttributeValue::Bool(false) => {
+                    continue;
+                }
+            };
+            let value = escape_attr(&value);
+            write!(html, r#" {name}="{value}""#)?;
         }
-        true
+        Ok(true)
     }
 
     fn write_opening_tag(
         &self,
         html: &mut String,
         tag: &str,
-        classes: &[String],
+        element: &Element,
     ) {
         html.push('<');
         html.push_str(tag);
-        if !classes.is_empty() {
+        if let Some(id) = &element.id {
+            html.push_str(" id=\"");
+            html.push_str(&escape_attr(id));
+            html.push('"');
+        }
+        if !element.classes.is_empty() {
             html.push_str(" class=\"");
-            for (i, class) in classes.iter().enumerate() {
+            for (i, class) in element.classes.iter().enumerate() {
                 if i > 0 {
                     html.push(' ');
                 }
@@ -271,13 +400,13 @@ impl<'p> PageWriter<'p> {
                 raw_html,
                 attributes,
             } => {
-                self.write_opening_tag(html, tag, &element.classes);
-                for (name, value) in attributes {
-                    if let Some(value) = value.as_str() {
-                        let value = escape_attr(value);
-                        write!(html, r#" {name}="{value}""#)?;
-                    }
+                let mut attributes_html = String::new();
+                if !self.write_attributes(&mut attributes_html, attributes)? {
+                    // failed expansion (eg `src: --logo` with no logo configured)
+                    return Ok(());
                 }
+                self.write_opening_tag(html, tag, element);
+                html.push_str(&attributes_html);
                 html.push_str(">\n");
                 if let Some(text) = text {
                     self.write_text(html, text);
@@ -287,8 +416,17 @@ impl<'p> PageWriter<'p> {
                 }
                 self.write_closing_tag(html, tag);
             }
-            ElementContent::DomTree { tag, children } => {
-                self.write_opening_tag(html, tag, &element.classes);
+            ElementContent::DomTree {
+                tag,
+                children,
+                attributes,
+            } => {
+                let mut attributes_html = String::new();
+                if !self.write_attributes(&mut attributes_html, attributes)? {
+                    return Ok(());
+                }
+                self.write_opening_tag(html, tag, element);
+                html.push_str(&attributes_html);
                 html.push_str(">\n");
                 for child in children {
                     self.write_element(html, child)?;
@@ -296,16 +434,23 @@ impl<'p> PageWriter<'p> {
                 self.write_closing_tag(html, tag);
             }
             ElementContent::Link(link) => {
-                self.write_nav_link(html, &element.classes, link)?;
+                self.write_nav_link(html, element.id.as_deref(), &element.classes, link)?;
+            }
+            ElementContent::Include(include) => {
+                self.write_include(html, include)?;
             }
             ElementContent::Menu(menu_insert) => {
                 self.config().site_map.push_nav(
                     html,
+                    element.id.as_deref(),
                     &element.classes,
                     menu_insert,
                     self.page_path(),
                 )?;
             }
+            ElementContent::ChildPages(child_pages) => {
+                self.write_child_pages(html, element, child_pages)?;
+            }
             ElementContent::Toc(toc) => {
                 html.push_str("<nav class=page-toc>\n");
                 html.push_str("<a class=toc-title href=\"#top\">");
@@ -336,9 +481,212 @@ impl<'p> PageWriter<'p> {
         Ok(())
     }
 
+    /// Write the list of the other pages of the section of the current page
+    /// (i.e. the site map or the sub menu in which the page is listed)
+    fn write_child_pages(
+        &self,
+        html: &mut String,
+        element: &Element,
+        child_pages: &ChildPages,
+    ) -> DdResult<()> {
+        for (value, message) in &child_pages.invalid_values {
+            // the element is in a config, not in the Markdown of the page
+            let mut diagnostic = Diagnostic::error(message.clone());
+            if let Some(location) = self.project.locate_in_configs(value) {
+                diagnostic = diagnostic.at(location);
+            }
+            self.report(diagnostic);
+        }
+        let Some(section) = self.project.section_of(self.page_path()) else {
+            return Ok(());
+        };
+        let (tag, class) = match child_pages.layout {
+            ChildPagesLayout::List => ("ul", "page-list"),
+            ChildPagesLayout::Cards => ("div", "page-cards"),
+        };
+        write!(html, "<{tag}")?;
+        if let Some(id) = &element.id {
+            write!(html, " id=\"{}\"", escape_attr(id))?;
+        }
+        write!(html, " class=\"{class}")?;
+        for class in &element.classes {
+            html.push(' ');
+            html.push_str(class);
+        }
+        html.push_str("\">\n");
+        match child_pages.layout {
+            ChildPagesLayout::List => {
+                self.write_page_list_items(html, section, child_pages.depth)?;
+            }
+            ChildPagesLayout::Cards => {
+                self.write_page_cards(html, section, child_pages.depth)?;
+            }
+        }
+        self.write_closing_tag(html, tag);
+        Ok(())
+    }
+
+    /// Return the link to the page an item of the site map leads to, and the description
+    /// of this page, or `None` if the item is the current page or leads to no page
+    fn child_page_link(
+        &self,
+        item: &ListItem,
+    ) -> Option<(String, Option<String>)> {
+        let page_path = item.first_page_path()?;
+        if matches!(item, ListItem::Page(_)) && &page_path == self.page_path() {
+            return None;
+        }
+        let link = self.page_path().link_to(&page_path);
+        let description = self
+            .project
+            .pages
+            .get(&page_path)
+            .and_then(|page| page.description.clone());
+        Some((link, description))
+    }
+
+    fn write_page_list_items(
+        &self,
+        html: &mut String,
+        list: &PageList,
+        depth: usize,
+    ) -> DdResult<()> {
+        for (title, item) in &list.items {
+            let Some((link, description)) = self.child_page_link(item) else {
+                continue;
+            };
+            writeln!(
+                html,
+                "<li class=\"page-list-item\"><a href=\"{}\">{}</a>",
+                link,
+                escape_text(title),
+            )?;
+            if let Some(description) = description {
+                writeln!(
+                    html,
+                    "<p class=\"page-description\">{}</p>",
+                    escape_text(&description),
+                )?;
+            }
+            if let ListItem::List(submenu) = item
+                && depth > 1
+            {
+                html.push_str("<ul class=\"page-list\">\n");
+                self.write_page_list_items(html, submenu, depth - 1)?;
+                html.push_str("</ul>\n");
+            }
+            html.push_str("</li>\n");
+        }
+        Ok(())
+    }
+
+    /// Write a card per item of the list, the cards of the items of a sub
+    /// menu following the card of the sub menu when `depth` allows it
+    fn write_page_cards(
+        &self,
+        html: &mut String,
+        list: &PageList,
+        depth: usize,
+    ) -> DdResult<()> {
+        for (title, item) in &list.items {
+            let Some((link, description)) = self.child_page_link(item) else {
+                continue;
+            };
+            writeln!(html, "<a class=\"page-card\" href=\"{}\">", link)?;
+            writeln!(
+                html,
+                "<span class=\"page-card-title\">{}</span>",
+                escape_text(title),
+            )?;
+            if let Some(description) = description {
+                writeln!(
+                    html,
+                    "<span class=\"page-description\">{}</span>",
+                    escape_text(&description),
+                )?;
+            }
+            html.push_str("</a>\n");
+            if let ListItem::List(submenu) = item
+                && depth > 1
+            {
+                html.push_str("<div class=\"page-cards\">\n");
+                self.write_page_cards(html, submenu, depth - 1)?;
+                html.push_str("</div>\n");
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the content of a file included with `ddoc-include`
+    ///
+    /// # Errors
+    /// Return `DdError` variants on write errors, not on missing or unsupported files
+    fn write_include(
+        &self,
+        html: &mut String,
+        include: &Include,
+    ) -> DdResult<()> {
+        let Some(path) = include.path(&self.project.src_path) else {
+            self.report_about(
+                &include.src,
+                Diagnostic::error(format!(
+                    "included file outside of the src directory: {}",
+                    include.src
+                )),
+            );
+            return Ok(());
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            self.report_about(
+                &include.src,
+                Diagnostic::error(format!("included file not found: {}", include.src)),
+            );
+            return Ok(());
+        };
+        if include.is_markdown() {
+            // the headings of included files aren't listed in the page TOC,
+            // but their IDs must not collide with the ones of the page
+            let rendered = render_markdown(
+                &content,
+                self.project,
+                self.page_path(),
+                &mut self.id_counts.borrow_mut(),
+            )?;
+            html.push_str(&rendered.html);
+        } else if include.is_html() {
+            let content = regex_replace_all!(
+                r"\{\{\s*(--[\w-]+)\s*\}\}",
+                &content,
+                |expansion: &str, token: &str| {
+                    let mut value = String::new();
+                    if self.write_text(&mut value, &Text::from(token)) {
+                        value
+                    } else {
+                        let mut diagnostic =
+                            Diagnostic::warning(format!("unresolved expansion: {expansion}"));
+                        if let Some(offset) = content.find(expansion) {
+                            diagnostic =
+                                diagnostic.at(Location::at_offset(&path, &content, offset));
+                        }
+                        self.report(diagnostic);
+                        expansion.to_string()
+                    }
+                }
+            );
+            html.push_str(&content);
+        } else {
+            self.report_about(
+                &include.src,
+                Diagnostic::error(format!("unsupported included file type: {}", include.src)),
+            );
+        }
+        Ok(())
+    }
+
     fn write_nav_link(
         &self,
         dest_html: &mut String,
+        id: Option<&str>,
         classes: &[String],
         link: &NavLink,
     ) -> DdResult<bool> {
@@ -352,6 +700,9 @@ impl<'p> PageWriter<'p> {
             }
             write!(html, " href=\"{url}\"")?;
         }
+        if let Some(id) = id {
+            write!(html, " id=\"{}\"", escape_attr(id))?;
+        }
         html.push_str(" class=\"nav-link ");
         for class in classes {
             html.push(' ');
@@ -387,10 +738,9 @@ impl<'p> PageWriter<'p> {
                             html.push_str(&content);
                         }
                         None => {
-                            eprintln!(
-                                "{}: file not found in ddoc-link configuration: {}",
-                                "error".red().bold(),
-                                src.clone().red(),
+                            self.report_about(
+                                src,
+                                Diagnostic::error(format!("inlined file not found: {src}")),
                             );
                         }
                     }
@@ -409,3 +759,168 @@ impl<'p> PageWriter<'p> {
         Ok(true)
     }
 }
+
+/// Render markdown as HTML, for a page or for a file included in a page.
+///
+/// Image sources and links are rewritten relative to the hosting page, IDs are
+/// generated for headings missing one, unique among the ones counted in
+/// `id_counts`, and the corresponding TOC items are written into the `toc`
+/// of the result.
+pub fn render_markdown(
+    md: &str,
+    project: &Project,
+    page_path: &PagePath,
+    id_counts: &mut FxHashMap<String, usize>,
+) -> DdResult<RenderedMarkdown> {
+    let mut rendered = RenderedMarkdown::default();
+    let mut events = Parser::new_ext(md, pcm::Options::all()).collect::<Vec<_>>();
+    for event in &mut events {
+        match event {
+            // Rewrite the image source
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                *dest_url = CowStr::from(project.img_url(dest_url, page_path));
+            }
+
+            // rewrite internal links
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                if let Some(new_url) = project.rewrite_link_url(dest_url, page_path) {
+                    *dest_url = CowStr::from(new_url);
+                }
+                rendered.links.push(dest_url.to_string());
+            }
+
+            Event::Start(Tag::Heading { .. }) => {
+                rendered.heading_count += 1;
+            }
+
+            _ => {}
+        }
+    }
+
+    // Generate IDs for headings if missing and
+    // generate the TOC's content
+    for HeadingId { level, id, text } in add_heading_ids(&mut events, id_counts) {
+        writeln!(
+            rendered.toc,
+            "<li class=\"toc-item {level}\"><a href=#{id}>{text}</a></li>"
+        )?;
+    }
+
+    rendered.search_sections = search_sections(&events);
+    push_html(&mut rendered.html, events.into_iter());
+    Ok(rendered)
+}
+
+/// Return the text of a heading, given the events following its start
+pub fn heading_text(events: &[Event]) -> String {
+    let mut heading_text = String::new();
+    for event in events {
+        match event {
+            Event::Code(text) | Event::Text(text) => {
+                if !heading_text.is_empty() {
+                    heading_text.push(' ');
+                }
+                heading_text.push_str(text);
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                break;
+            }
+            _ => {}
+        }
+    }
+    heading_text
+}
+
+/// A heading which was given a generated ID
+pub struct HeadingId {
+    pub level: HeadingLevel,
+    pub id: String,
+    pub text: String,
+}
+
+/// Give to the headings without ID one generated from their text, made
+/// unique with `id_counts`, and return those headings
+pub fn add_heading_ids(
+    events: &mut [Event],
+    id_counts: &mut FxHashMap<String, usize>,
+) -> Vec<HeadingId> {
+    let mut headings = Vec::new();
+    for i in 0..events.len() {
+        let Event::Start(Tag::Heading {
+            level, id: None, ..
+        }) = &events[i]
+        else {
+            continue;
+        };
+        let level = *level;
+        let text = heading_text(&events[i + 1..]);
+        let new_id = unique_heading_id(&text, id_counts);
+        if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+            *id = Some(CowStr::from(new_id.clone()));
+        }
+        headings.push(HeadingId {
+            level,
+            id: new_id,
+            text,
+        });
+    }
+    headings
+}
+
+/// Generate an ID from the heading text, made unique with a suffix
+/// when the same text was already met
+pub fn unique_heading_id(
+    heading_text: &str,
+    id_counts: &mut FxHashMap<String, usize>,
+) -> String {
+    let mut new_id = heading_text
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect::<String>();
+    let count = id_counts.entry(new_id.clone()).or_insert(0);
+    *count += 1;
+    if *count > 1 {
+        new_id = format!("{}-{}", new_id, count);
+    }
+    new_id
+}
+
+#[test]
+fn test_include_heading_ids() {
+    let test_project = TestProject::new("include");
+    test_project
+        .write("src/index.md", "# Usage\n")
+        .write("src/footer.md", "# Usage\n");
+    let project = test_project.load(
+        r#"{
+            title: Test
+            site-map: {
+                Home: index.md
+            }
+            body: {
+                ddoc-main: {}
+                footer: {
+                    ddoc-include: {
+                        src: footer.md
+                    }
+                }
+                aside: {
+                    ddoc-include: {
+                        src: ../../etc/passwd
+                    }
+                }
+            }
+        }"#,
+    );
+    let result = project.build().unwrap();
+    let html = test_project.output("index.html");
+    assert!(html.contains(r#"<h1 id="usage">Usage</h1>"#));
+    assert!(html.contains(r#"<h1 id="usage-2">Usage</h1>"#));
+    assert!(!html.contains("root:"));
+    assert!(result.diagnostics.iter().any(|d| {
+        d.message
+            .starts_with("included file outside of the src directory")
+    }));
+}
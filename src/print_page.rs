@@ -0,0 +1,257 @@
+use {
+    crate::*,
+    pulldown_cmark::{
+        self as pcm,
+        CowStr,
+        Event,
+        Parser,
+        Tag,
+        html::push_html,
+    },
+    rustc_hash::{
+        FxHashMap,
+        FxHashSet,
+    },
+    std::{
+        fmt::Write,
+        fs,
+    },
+};
+
+/// Stem of the path of the page containing all pages, built when
+/// `print-page` is set
+pub static PRINT_PAGE_STEM: &str = "all";
+
+/// Rules making each page of the print page start on a new sheet
+static PRINT_PAGE_STYLE: &str = "<style>
+@media print { .print-page-section { break-before: page; } }
+</style>
+";
+
+impl Project {
+    /// The path of the print page, `all`
+    pub fn print_page_path() -> PagePath {
+        PagePath::from_path_file("", PRINT_PAGE_STEM)
+    }
+
+    /// Build the HTML of the page containing all pages of the site, in the
+    /// order of the site map, preceded by a table of content
+    ///
+    /// Heading IDs are prefixed by the ID of their page, links to pages
+    /// are rewritten as links to their sections, and image sources are
+    /// rewritten for the path of the print page.
+    ///
+    /// # Errors
+    /// Return `DdError` variants on write errors, not on project config/data errors
+    pub fn write_print_page(
+        &self,
+        html: &mut String,
+    ) -> DdResult<()> {
+        let print_page_path = Self::print_page_path();
+        let page = Page::generated("All pages".to_string(), print_page_path.clone());
+        let page_writer = PageWriter::new(&page, self, "")?;
+        page_writer.write_html_head_with(html, PRINT_PAGE_STYLE)?;
+        html.push_str("<body class=\"page-all print-page\">\n<main>\n");
+        writeln!(
+            html,
+            "<h1 class=print-page-title>{}</h1>",
+            escape_text(self.config.title()),
+        )?;
+        html.push_str("<nav class=print-page-toc>\n");
+        let section_ids = self.print_section_ids();
+        let mut listed = FxHashSet::default();
+        write_print_toc(html, &self.config.site_map, &section_ids, &mut listed)?;
+        html.push_str("</nav>\n");
+        for page_path in self.nav().order() {
+            let Some(page) = self.pages.get(page_path) else {
+                continue;
+            };
+            let Ok(md) = fs::read_to_string(&page.md_file_path) else {
+                continue; // reported when rendering the page itself
+            };
+            writeln!(
+                html,
+                "<section class=print-page-section id=\"{}\">",
+                section_ids[page_path],
+            )?;
+            writeln!(
+                html,
+                "<h1 class=print-page-section-title>{}</h1>",
+                escape_text(&page.title),
+            )?;
+            html.push_str(&self.render_print_section(&md, page_path, &section_ids));
+            html.push_str("</section>\n");
+        }
+        html.push_str("</main>\n</body>\n</html>\n");
+        Ok(())
+    }
+
+    /// The IDs of the sections of the pages in the print page, eg
+    /// `guide-setup` for `guide/setup`, with a suffix when already taken
+    /// (eg by the top-level `guide-setup`)
+    fn print_section_ids(&self) -> FxHashMap<PagePath, String> {
+        let mut section_ids = FxHashMap::default();
+        let mut taken = FxHashSet::default();
+        for page_path in self.nav().order() {
+            if !self.pages.contains_key(page_path) || section_ids.contains_key(page_path) {
+                continue;
+            }
+            let base_id = page_path.to_string().replace('/', "-");
+            let mut section_id = base_id.clone();
+            let mut count = 1;
+            while !taken.insert(section_id.clone()) {
+                count += 1;
+                section_id = format!("{base_id}-{count}");
+            }
+            section_ids.insert(page_path.clone(), section_id);
+        }
+        section_ids
+    }
+
+    /// Render the Markdown of a page for the print page
+    ///
+    /// Problems (eg broken links) aren't reported, as they already are
+    /// when rendering the page itself.
+    fn render_print_section(
+        &self,
+        md: &str,
+        page_path: &PagePath,
+        section_ids: &FxHashMap<PagePath, String>,
+    ) -> String {
+        let section_id = &section_ids[page_path];
+        let mut id_counts = FxHashMap::default();
+        let mut events = Parser::new_ext(md, pcm::Options::all()).collect::<Vec<_>>();
+        add_heading_ids(&mut events, &mut id_counts);
+        for event in &mut events {
+            match event {
+                Event::Start(Tag::Heading { id: Some(id), .. }) => {
+                    *id = CowStr::from(format!("{section_id}--{id}"));
+                }
+                Event::Start(Tag::Image { dest_url, .. }) => {
+                    *dest_url = CowStr::from(self.print_img_url(dest_url));
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    if let Some(url) = self.print_link_url(dest_url, page_path, section_ids) {
+                        *dest_url = CowStr::from(url);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut html = String::new();
+        push_html(&mut html, events.into_iter());
+        html
+    }
+
+    /// Rewrite the source of an image for the print page, which is one
+    /// level deep
+    fn print_img_url(
+        &self,
+        src: &str,
+    ) -> String {
+        match self.img_src_path(src) {
+            Some((_, path)) => format!("../{}", self.asset_path(&path)),
+            None => self.expand_var(src).into_owned(),
+        }
+    }
+
+    /// Rewrite a link of a page as a link to a section, or heading,
+    /// of the print page, if it targets a page of the site, after
+    /// the expansion of a `--name`
+    fn print_link_url(
+        &self,
+        src: &str,
+        page_path: &PagePath,
+        section_ids: &FxHashMap<PagePath, String>,
+    ) -> Option<String> {
+        if let Some(var_name) = src.strip_prefix("--") {
+            return match self.expand_link_var(var_name, page_path)? {
+                LinkVar::Value(value) => Some(
+                    self.print_link_url(&value, page_path, section_ids)
+                        .unwrap_or(value),
+                ),
+                LinkVar::Page(target) => section_ids.get(target).map(|id| format!("#{id}")),
+            };
+        }
+        if let Some(hash) = src.strip_prefix('#') {
+            return Some(format!("#{}--{hash}", section_ids[page_path]));
+        }
+        let target = self.internal_link_target(src, page_path)?;
+        let section_id = section_ids.get(&target)?;
+        Some(match src.split_once('#') {
+            Some((_, hash)) if !hash.is_empty() => format!("#{section_id}--{hash}"),
+            _ => format!("#{section_id}"),
+        })
+    }
+}
+
+/// Write the table of content of the print page, with the structure of
+/// the site map, and links only for the first occurrence of each page
+fn write_print_toc(
+    html: &mut String,
+    list: &PageList,
+    section_ids: &FxHashMap<PagePath, String>,
+    listed: &mut FxHashSet<PagePath>,
+) -> DdResult<()> {
+    html.push_str("<ul>\n");
+    for (title, item) in &list.items {
+        let title = escape_text(title);
+        match item {
+            ListItem::Page(page_path) if listed.insert(page_path.clone()) => {
+                if let Some(section_id) = section_ids.get(page_path) {
+                    writeln!(html, "<li><a href=\"#{section_id}\">{title}</a></li>")?;
+                }
+            }
+            ListItem::Page(_) => {}
+            ListItem::List(submenu) => {
+                writeln!(html, "<li><span>{title}</span>")?;
+                write_print_toc(html, submenu, section_ids, listed)?;
+                html.push_str("</li>\n");
+            }
+        }
+    }
+    html.push_str("</ul>\n");
+    Ok(())
+}
+
+#[test]
+fn test_print_page() {
+    let test_project = TestProject::new("print");
+    test_project
+        .write(
+            "src/index.md",
+            "# Intro\n\nSee [the setup](guide/setup.md#install), [next](--next), [repo](--repo).\n",
+        )
+        .write(
+            "src/guide/setup.md",
+            "# Install\n\n![logo](../img/logo.png)\n",
+        )
+        .write("src/guide-setup.md", "# Other\n");
+    let project = test_project.load(
+        r#"{
+            title: Test
+            print-page: true
+            vars: {
+                repo: https://example.com/repo
+            }
+            site-map: {
+                Home: index.md
+                Other: guide-setup.md
+                Guide: {
+                    Setup: guide/setup.md
+                }
+            }
+        }"#,
+    );
+    project.build().unwrap();
+    let html = test_project.output("all/index.html");
+    assert!(html.contains("</style>\n</head>\n<body"));
+    assert!(html.contains(r##"<li><a href="#guide-setup">Other</a></li>"##));
+    assert!(html.contains(r##"<li><a href="#guide-setup-2">Setup</a></li>"##));
+    assert!(html.contains(r#"<section class=print-page-section id="guide-setup-2">"#));
+    assert!(html.contains(r#"<h1 id="guide-setup-2--install">Install</h1>"#));
+    assert!(html.contains(r##"<a href="#guide-setup-2--install">the setup</a>"##));
+    assert!(html.contains(r##"<a href="#guide-setup">next</a>"##));
+    assert!(html.contains(r#"<a href="https://example.com/repo">repo</a>"#));
+    assert!(html.contains(r#"<img src="../img/logo.png""#));
+}
@@ -1,41 +1,93 @@
 use {
     crate::*,
     lazy_regex::regex_captures,
+    rayon::prelude::*,
     rustc_hash::FxHashMap,
     std::{
         borrow::Cow,
+        fmt::Write,
         fs,
-        io::Write,
         path::{
             Path,
             PathBuf,
         },
+        sync::{
+            Mutex,
+            MutexGuard,
+        },
+        time::Instant,
     },
     termimad::crossterm::style::Stylize,
+    xxhash_rust::xxh3::xxh3_64,
 };
 
 /// A ddoc project, with its configuration, pages, and
 /// location which allows building it.
 pub struct Project {
     pub root: PathBuf,
+    /// What was changed with a `ProjectBuilder`
+    settings: ProjectSettings,
     pub src_path: PathBuf,
     pub build_path: PathBuf,
+    /// Where the files go when no custom sink is set
+    fs_sink: FsSink,
     pub config: Config,
     modules: Vec<Module>,
     pub pages: FxHashMap<PagePath, Page>,
+    /// The navigation structure, computed from the site map
+    nav: NavIndex,
+    /// The JS files to link in the head of pages
+    js_entries: Vec<StaticEntry>,
+    /// The CSS files to link in the head of pages
+    css_entries: Vec<StaticEntry>,
+    /// The content-hashed names of the assets, empty unless `fingerprint-assets` is set
+    fingerprints: AssetFingerprints,
+    /// The bundles linked instead of the CSS and JS files, when `bundle-assets` is set
+    css_bundle: Option<Bundle>,
+    js_bundle: Option<Bundle>,
+    /// Problems found while loading the project, which didn't prevent it
+    pub load_diagnostics: Vec<Diagnostic>,
+    /// Problems found while rendering pages, with the page they were found in
+    page_diagnostics: Mutex<Vec<(PagePath, Diagnostic)>>,
+    /// The text of the rendered pages, for the search index
+    search_sections: Mutex<FxHashMap<PagePath, Vec<SearchSection>>>,
+    /// What's in the build directory
+    manifest: Mutex<BuildManifest>,
 }
 
 impl Project {
     /// Given the path to a ddoc project root,
     /// load its configuration and pages into a `Project` struct.
+    ///
+    /// Use a `ProjectBuilder` for more options.
     pub fn load(path: &Path) -> DdResult<Self> {
+        ProjectBuilder::new(path).load()
+    }
+    /// Load the project at `root`, with the settings of a `ProjectBuilder`
+    pub fn load_with(
+        root: PathBuf,
+        settings: ProjectSettings,
+    ) -> DdResult<Self> {
         let mut project = Self {
-            root: path.to_owned(),
+            root,
+            settings,
+            fs_sink: Default::default(),
             config: Default::default(),
             modules: Default::default(),
             pages: Default::default(),
-            src_path: path.join("src"),
-            build_path: path.join("site"),
+            nav: Default::default(),
+            js_entries: Default::default(),
+            css_entries: Default::default(),
+            fingerprints: Default::default(),
+            css_bundle: None,
+            js_bundle: None,
+            load_diagnostics: Default::default(),
+            page_diagnostics: Default::default(),
+            search_sections: Default::default(),
+            manifest: Default::default(),
+            // both paths are set from the config, on load
+            src_path: Default::default(),
+            build_path: Default::default(),
         };
         project.load_content()?;
         Ok(project)
@@ -63,36 +115,68 @@ impl Project {
         // clean
         self.modules = Vec::new();
         self.pages.clear();
+        self.load_diagnostics.clear();
 
         // load all modules, including the main
-        let main_module = Module::load("", &self.root)?;
-        let mut config = main_module
-            .config
-            .clone()
-            .ok_or(DdError::ConfigNotFound)?
-            .take_entity();
+        // when the config is given, the config file isn't even looked for
+        let (mut main_module, mut config) = match &self.settings.config {
+            Some(config) => (Module::new("", &self.root, None), config.clone()),
+            None => {
+                let main_module = Module::load("", &self.root)?;
+                let main_config = main_module.config.clone().ok_or(DdError::ConfigNotFound)?;
+                self.load_diagnostics
+                    .extend(check::unknown_key_diagnostics(main_config.src()));
+                (main_module, main_config.take_entity())
+            }
+        };
+        let dir_overrides = &self.settings.dir_overrides;
+        self.src_path = self.root.join(
+            dir_overrides
+                .src
+                .as_deref()
+                .or(config.src_dir.as_deref().map(Path::new))
+                .unwrap_or(Path::new(DEFAULT_SRC_DIR)),
+        );
+        main_module.src_path = self.src_path.clone();
+        let build_path = self.root.join(
+            dir_overrides
+                .build
+                .as_deref()
+                .or(config.build_dir.as_deref().map(Path::new))
+                .unwrap_or(Path::new(DEFAULT_BUILD_DIR)),
+        );
+        check_build_dir(&self.root, &self.src_path, &build_path)?;
+        if build_path != self.build_path {
+            self.build_path = build_path;
+            self.fs_sink = FsSink::new(&self.build_path);
+            *self.manifest() = BuildManifest::read(self.sink()).unwrap_or_default();
+        }
         let active_plugins = config.active_plugins.clone();
         self.modules.push(main_module);
         for name in &active_plugins {
             let plugin_root = self.root.join("plugins").join(name);
             if !plugin_root.exists() {
-                eprintln!(
-                    "{}: plugin '{}' not found at expected path {:?}",
-                    "error".red().bold(),
-                    name.to_string().red(),
-                    plugin_root,
-                );
+                let mut diagnostic = Diagnostic::error(format!(
+                    "plugin '{}' not found at expected path {:?}",
+                    name, plugin_root,
+                ));
+                if let Some(location) = self.locate_in_config(&["active-plugins", name]) {
+                    diagnostic = diagnostic.at(location);
+                }
                 if plugin_is_known(name) {
-                    eprintln!(
-                        " Plugin '{}' is known, but not found in the project.\n You can initialize it with {}?",
-                        name.to_string().yellow(),
+                    diagnostic = diagnostic.with_help(format!(
+                        "plugin '{}' is known, you can initialize it with {}",
+                        name,
                         format!("ddoc --init-plugin {}", name).green().bold(),
-                    );
+                    ));
                 }
+                self.load_diagnostics.push(diagnostic);
                 continue;
             }
             let plugin_module = Module::load(name, &plugin_root)?;
             if let Some(plugin_config) = &plugin_module.config {
+                self.load_diagnostics
+                    .extend(check::unknown_key_diagnostics(plugin_config.src()));
                 config.merge(plugin_config.as_ref());
             }
             // TODO merge plugin config into main config
@@ -102,23 +186,273 @@ impl Project {
         // fix and apply config
         compat::fix_old_config(&mut config);
         config.site_map.add_pages(self);
+        self.nav = NavIndex::new(&config.site_map);
 
         // store it
         self.config = config;
+        if self.config.print_page && self.pages.contains_key(&Self::print_page_path()) {
+            let mut diagnostic = Diagnostic::warning(format!(
+                "the print page isn't built, as there's already a page at {PRINT_PAGE_STEM}/"
+            ));
+            if let Some(location) = self.locate_in_config(&["print-page"]) {
+                diagnostic = diagnostic.at(location);
+            }
+            self.load_diagnostics.push(diagnostic);
+        }
+
+        // list the statics linked from all pages, once for all the builds
+        // until the next reload
+        self.js_entries.clear();
+        self.css_entries.clear();
+        for module in &self.modules {
+            module.list_js(&mut self.js_entries, &mut self.load_diagnostics)?;
+            module.list_css(&mut self.css_entries, &mut self.load_diagnostics)?;
+        }
+        let mut fingerprints = if self.config.fingerprint_assets {
+            AssetFingerprints::compute(&self.static_files()?)?
+        } else {
+            AssetFingerprints::default()
+        };
+        self.css_bundle = None;
+        self.js_bundle = None;
+        if self.config.bundle_assets {
+            // CSS files are linked in reverse order
+            let css = Bundle::css(self.css_entries.iter().rev(), &self.root, &fingerprints)?;
+            let js = Bundle::js(self.js_entries.iter(), &self.root)?;
+            if self.config.fingerprint_assets {
+                fingerprints.add(css.path, css.content.as_bytes());
+                fingerprints.add(js.path, js.content.as_bytes());
+            }
+            self.css_bundle = Some(css);
+            self.js_bundle = Some(js);
+        }
+        self.fingerprints = fingerprints;
         Ok(())
     }
 
-    /// Fills the 'site' directory with the generated HTML files and static files
+    /// List the files of the js, css, and img directories of all modules,
+    /// keyed by their path relative to the site root
     ///
-    /// Don't do any prealable cleaning, call `clean_build_dir` first if needed.
-    pub fn build(&self) -> DdResult<()> {
+    /// When several modules have the same file, the last one wins.
+    pub fn static_files(&self) -> DdResult<FxHashMap<String, PathBuf>> {
+        let mut static_files = Vec::new();
         for module in &self.modules {
-            module.copy_all_statics_into(&self.build_path)?;
+            module.list_static_files(&mut static_files)?;
+        }
+        Ok(static_files.into_iter().collect())
+    }
+
+    /// Fills the 'site' directory, or the custom sink, with the generated
+    /// HTML files and static files
+    ///
+    /// Files which are already up to date, according to the build manifest,
+    /// aren't written again, and the files of previous builds which aren't
+    /// produced anymore are removed.
+    ///
+    /// Files not produced by ddoc are never removed.
+    ///
+    /// The problems found in pages don't make the build fail, they're
+    /// returned in the `BuildResult`.
+    pub fn build(&self) -> DdResult<BuildResult> {
+        self.manifest().start_build();
+        let mut assets = Vec::new();
+        for (path, src_path) in &self.static_files()? {
+            let build_path = self.fingerprints.resolve(path);
+            assets.push(build_path.to_string());
+            let is_css = path.ends_with(".css");
+            let is_js = path.ends_with(".js");
+            if self.settings.minify && (is_css || is_js) {
+                let content = match self.fingerprints.css_content(path) {
+                    Some(css) => Cow::Borrowed(css),
+                    None => Cow::Owned(fs::read_to_string(src_path)?),
+                };
+                let content = if is_css {
+                    minify_css(&content)
+                } else {
+                    minify_js(&content)
+                };
+                let record = FileRecord::generated(content.as_bytes()).with_source(src_path);
+                self.write_to_build(build_path, content.as_bytes(), record)?;
+            } else if let Some(css) = self.fingerprints.css_content(path) {
+                let record = FileRecord::generated(css.as_bytes());
+                self.write_to_build(build_path, css.as_bytes(), record)?;
+            } else {
+                self.copy_to_build(build_path, src_path)?;
+            }
+        }
+        for bundle in self.css_bundle.iter().chain(&self.js_bundle) {
+            let content = match (self.settings.minify, bundle.path.ends_with(".css")) {
+                (false, _) => Cow::Borrowed(&bundle.content),
+                (true, true) => Cow::Owned(minify_css(&bundle.content)),
+                (true, false) => Cow::Owned(minify_js(&bundle.content)),
+            };
+            let record = FileRecord::generated(content.as_bytes());
+            let build_path = self.fingerprints.resolve(bundle.path);
+            assets.push(build_path.to_string());
+            self.write_to_build(build_path, content.as_bytes(), record)?;
+        }
+        if !self.fingerprints.is_empty() {
+            let json = self.fingerprints.manifest_json()?;
+            let record = FileRecord::generated(json.as_bytes());
+            self.write_to_build(ASSET_MANIFEST_FILE_NAME, json.as_bytes(), record)?;
+        }
+        let mut extra_files = Vec::new();
+        for extra_static in &self.config.extra_statics {
+            extra_static.list_files(&self.src_path, &mut extra_files)?;
         }
+        for (path, src_path) in &extra_files {
+            assets.push(path.clone());
+            self.copy_to_build(path, src_path)?;
+        }
+        assets.sort();
         before_0_16::write_special_js_files_if_needed(&self.config, self)?;
-        for page_path in self.pages.keys() {
-            self.build_page(page_path)?;
+        // pages are rendered in parallel, but errors are reported in
+        // the order of the site map
+        let inputs = self.render_inputs()?;
+        let results: Vec<DdResult<Option<PageReport>>> = self
+            .nav
+            .order()
+            .par_iter()
+            .map(|page_path| self.render_page(page_path, &inputs))
+            .collect();
+        self.build_print_page()?;
+        self.build_llms_txt()?;
+        self.build_search_index()?;
+        let mut diagnostics = self.load_diagnostics.clone();
+        diagnostics.extend(self.take_page_diagnostics());
+        let pages = results
+            .into_iter()
+            .collect::<DdResult<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut manifest = self.manifest();
+        let pruned = manifest.prune(self.sink())?;
+        if pruned > 0 {
+            info!("removed {pruned} stale files from the build directory");
+        }
+        manifest.write(self.sink())?;
+        Ok(BuildResult {
+            files: manifest.files.keys().cloned().collect(),
+            pages,
+            assets,
+            plugins: self.plugin_names().map(str::to_string).collect(),
+            diagnostics,
+        })
+    }
+    /// Run the pre-build hooks, reload the project if there were any (as they
+    /// may have generated pages or static files), build the site, then run
+    /// the post-build hooks
+    ///
+    /// Hook failures are added to the diagnostics of the result. When a
+    /// pre-build hook fails, the site is still built, with what's there,
+    /// but the post-build hooks aren't run.
+    pub fn build_with_hooks(&mut self) -> DdResult<BuildResult> {
+        let mut hook_diagnostics = self.run_pre_build_hooks()?;
+        let mut result = self.build()?;
+        if hook_diagnostics.is_empty() {
+            hook_diagnostics = self.run_hooks(HookStage::PostBuild);
+        }
+        result.diagnostics.extend(hook_diagnostics);
+        Ok(result)
+    }
+    /// Run the pre-build hooks, if any, then reload the project, whose
+    /// files they may have changed, and return the problems met
+    pub fn run_pre_build_hooks(&mut self) -> DdResult<Vec<Diagnostic>> {
+        if self.config.hooks.pre_build.is_empty() {
+            return Ok(Vec::new());
+        }
+        let diagnostics = self.run_hooks(HookStage::PreBuild);
+        self.load_content()?;
+        Ok(diagnostics)
+    }
+    /// Build the page containing all pages, if `print-page` is set
+    fn build_print_page(&self) -> DdResult<()> {
+        if !self.config.print_page || self.pages.contains_key(&Self::print_page_path()) {
+            return Ok(());
+        }
+        let mut html = String::new();
+        self.write_print_page(&mut html)?;
+        if self.settings.minify {
+            html = minify_html(&html);
+        }
+        let html_path = Self::print_page_path().html_path_buf(Path::new(""));
+        let record = FileRecord::generated(html.as_bytes());
+        self.write_to_build(&html_path.to_string_lossy(), html.as_bytes(), record)
+    }
+    /// Build `llms.txt` and `llms-full.txt`, if `llms-txt` is set
+    fn build_llms_txt(&self) -> DdResult<()> {
+        if !self.config.llms_txt {
+            return Ok(());
+        }
+        let mut txt = String::new();
+        self.write_llms_txt(&mut txt)?;
+        let record = FileRecord::generated(txt.as_bytes());
+        self.write_to_build(LLMS_TXT_FILE_NAME, txt.as_bytes(), record)?;
+        txt.clear();
+        self.write_llms_full_txt(&mut txt)?;
+        let record = FileRecord::generated(txt.as_bytes());
+        self.write_to_build(LLMS_FULL_TXT_FILE_NAME, txt.as_bytes(), record)
+    }
+    /// Build the search index, if the `search` plugin is active
+    fn build_search_index(&self) -> DdResult<()> {
+        if !self.has_search_index() {
+            return Ok(());
+        }
+        let json = self.search_index_json()?;
+        let record = FileRecord::generated(json.as_bytes());
+        self.write_to_build(SEARCH_INDEX_FILE_NAME, json.as_bytes(), record)
+    }
+    /// The sections of the rendered pages, for the search index
+    pub fn page_search_sections(&self) -> MutexGuard<'_, FxHashMap<PagePath, Vec<SearchSection>>> {
+        self.search_sections
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+    /// Tell whether the HTML pages and the CSS and JS files are minified
+    pub fn minify(&self) -> bool {
+        self.settings.minify
+    }
+    /// Return where the files of the site go
+    pub fn sink(&self) -> &dyn OutputSink {
+        match &self.settings.sink {
+            Some(sink) => sink.as_ref(),
+            None => &self.fs_sink,
         }
+    }
+    fn manifest(&self) -> MutexGuard<'_, BuildManifest> {
+        self.manifest
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+    /// Write a generated file in the build directory, unless it's already there
+    fn write_to_build(
+        &self,
+        path: &str, // relative to the build directory
+        content: &[u8],
+        record: FileRecord,
+    ) -> DdResult<()> {
+        let mut manifest = self.manifest();
+        if manifest.is_up_to_date(self.sink(), path, &record) {
+            return Ok(());
+        }
+        self.sink().write(path, content)?;
+        manifest.record(path.to_string(), record);
+        Ok(())
+    }
+    /// Copy a static file in the build directory, unless it's already there
+    fn copy_to_build(
+        &self,
+        path: &str, // relative to the build directory
+        src_path: &Path,
+    ) -> DdResult<()> {
+        let record = FileRecord::copied(src_path)?;
+        let mut manifest = self.manifest();
+        if manifest.is_up_to_date(self.sink(), path, &record) {
+            return Ok(());
+        }
+        self.sink().copy(path, src_path)?;
+        manifest.record(path.to_string(), record);
         Ok(())
     }
     pub fn add_js_to_build(
@@ -126,14 +460,8 @@ impl Project {
         filename: &str,
         bytes: &[u8],
     ) -> DdResult<()> {
-        let js_path = self.build_path.join("js").join(filename);
-        if !js_path.exists() {
-            if let Some(parent) = js_path.parent() {
-                fs::create_dir_all(parent)?;
-            }
-            fs::write(&js_path, bytes)?;
-        }
-        Ok(())
+        let path = format!("js/{filename}");
+        self.write_to_build(&path, bytes, FileRecord::generated(bytes))
     }
     /// Try to update the project. Return true when some real work was done.
     ///
@@ -153,37 +481,72 @@ impl Project {
             }
             FileChange::Removal(touched_path) => {
                 // we care only if it's a CSS or JS file (header may have changed)
-                if let Ok(rel_path) = touched_path.strip_prefix(&self.src_path) {
-                    if rel_path.starts_with("css/") || rel_path.starts_with("js/") {
-                        self.reload_and_rebuild(base_url)?;
-                        return Ok(true);
-                    }
+                // or a config file (which may have been renamed to another format)
+                if Config::is_config_file(&touched_path) {
+                    self.reload_and_rebuild(base_url)?;
+                    return Ok(true);
+                }
+                if let Ok(rel_path) = touched_path.strip_prefix(&self.src_path)
+                    && (rel_path.starts_with("css/") || rel_path.starts_with("js/"))
+                {
+                    self.reload_and_rebuild(base_url)?;
+                    return Ok(true);
                 }
             }
             FileChange::Write(touched_path) => {
-                // partial update for /src/img/ files and /src/*.md files
-                if let Ok(rel_path) = touched_path.strip_prefix(&self.src_path) {
+                // partial update for /src/img/ files and /src/*.md files, unless
+                // there are hooks, which must run around each build
+                if let Ok(rel_path) = touched_path.strip_prefix(&self.src_path)
+                    && self.config.hooks.is_empty()
+                {
                     let ext = rel_path.extension().and_then(|s| s.to_str());
                     if ext == Some("md") {
-                        for (page_path, page) in &self.pages {
-                            if page.md_file_path == touched_path {
-                                info!("Modified page {:?}", page_path);
-                                let url = page_path.to_absolute_url(base_url);
-                                eprintln!("Modified {}", url.yellow());
-                                self.build_page(page_path)?;
-                                return Ok(true);
+                        let page = self
+                            .pages
+                            .values_mut()
+                            .find(|page| page.md_file_path == touched_path);
+                        if let Some(page) = page {
+                            let page_path = page.page_path.clone();
+                            // the description of a page is shown in the
+                            // page lists of the other pages
+                            let description_changed = page.reload_description();
+                            info!("Modified page {:?}", page_path);
+                            let url = page_path.to_absolute_url(base_url);
+                            eprintln!("Modified {}", url.yellow());
+                            if description_changed {
+                                self.build_pages(self.nav.order())?;
+                            } else {
+                                self.build_page(&page_path)?;
                             }
+                            self.build_print_page()?;
+                            self.build_llms_txt()?;
+                            self.build_search_index()?;
+                            self.manifest().write(self.sink())?;
+                            return Ok(true);
+                        }
+                        let rel_path = rel_path.to_string_lossy();
+                        if self.config.body.includes(&rel_path) {
+                            info!("Modified included file {rel_path:?}");
+                            eprintln!("Modified included file {}", rel_path.yellow());
+                            self.build_pages(self.nav.order())?;
+                            self.build_print_page()?;
+                            self.build_llms_txt()?;
+                            self.build_search_index()?;
+                            self.manifest().write(self.sink())?;
+                            return Ok(true);
                         }
                         return Ok(false); // might be a readme, etc.
                     }
-                    if let Ok(rel_img) = rel_path.strip_prefix("img/") {
+                    // with fingerprinted assets, the name of the image changes
+                    // so all pages, and CSS files, must be rebuilt
+                    if let Ok(rel_img) = rel_path.strip_prefix("img/")
+                        && !self.config.fingerprint_assets
+                    {
                         info!("Deployed image {rel_img:?}");
                         eprintln!("Deployed image {}", rel_img.to_string_lossy().yellow());
-                        let dst_path = self.build_path.join("img").join(rel_img);
-                        if let Some(parent) = dst_path.parent() {
-                            fs::create_dir_all(parent)?;
-                        }
-                        fs::copy(&touched_path, &dst_path)?;
+                        let path = format!("img/{}", rel_img.to_string_lossy());
+                        self.copy_to_build(&path, &touched_path)?;
+                        self.manifest().write(self.sink())?;
                         return Ok(true);
                     }
                 }
@@ -204,13 +567,17 @@ impl Project {
         info!("full rebuild");
         eprintln!("Full rebuild of {}", base_url.yellow());
         match self.load_content() {
-            Ok(()) => {
-                self.build()?;
-            }
+            Ok(()) => match self.build_with_hooks() {
+                Ok(result) => result.print_diagnostics(&self.root),
+                Err(e) => {
+                    self.print_load_diagnostics();
+                    return Err(e);
+                }
+            },
             Err(DdError::ConfigNotFound) => eprintln!(
-                "{}: could not read updated config file at {:?}, keeping the old one.",
+                "{}: could not find the config file in {:?}, keeping the old one.",
                 "warning".yellow().bold(),
-                self.root.join(CONFIG_FILE_NAME),
+                self.root,
             ),
             Err(e) => eprintln!(
                 "{}: failed to reload the project: {e}",
@@ -219,17 +586,64 @@ impl Project {
         }
         Ok(())
     }
-    /// remove the 'build' directory and its content
+    /// Print the problems found while loading the project
+    pub fn print_load_diagnostics(&self) {
+        for diagnostic in &self.load_diagnostics {
+            diagnostic.print(&self.root);
+        }
+    }
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+    /// Return the path to the config file of the main module
+    pub fn config_path(&self) -> Option<&Path> {
+        self.modules
+            .iter()
+            .find(|m| m.is_main())
+            .and_then(|m| m.config.as_ref())
+            .map(|c| c.src())
+    }
+    /// Locate in the main config file the value or key at the end of the given path of tokens
+    pub fn locate_in_config(
+        &self,
+        tokens: &[&str],
+    ) -> Option<Location> {
+        let config_path = self.config_path()?;
+        check::locate_in_file(config_path, tokens)
+    }
+    /// Tell whether the file at the given path, relative to the src directory,
+    /// exists in the main project or in one of the plugins
+    pub fn static_source_exists(
+        &self,
+        path: &str,
+    ) -> bool {
+        self.modules
+            .iter()
+            .any(|module| module.src_path.join(path).exists())
+    }
+    /// Remove from the build directory the files recorded in the manifest
+    /// of a previous build, even one made by another version of ddoc
+    ///
+    /// Files not written by ddoc are never removed: without a manifest,
+    /// nothing is.
     pub fn clean_build_dir(&self) -> DdResult<()> {
-        if self.build_path.exists() {
-            fs::remove_dir_all(&self.build_path)?;
+        let mut manifest = self.manifest();
+        if let Some(previous) = BuildManifest::read_any_version(self.sink()) {
+            for path in previous.files.keys() {
+                self.sink().remove(path)?;
+            }
         }
+        *manifest = BuildManifest::default();
         Ok(())
     }
-    pub fn load_and_build(path: &Path) -> DdResult<()> {
+    /// Tell whether the build directory has a manifest of a previous build
+    /// by this version of ddoc, which allows an incremental build
+    pub fn has_build_manifest(&self) -> bool {
+        BuildManifest::read(self.sink()).is_some()
+    }
+    pub fn load_and_build(path: &Path) -> DdResult<BuildResult> {
         let project = Self::load(path)?;
-        project.build()?;
-        Ok(())
+        project.build()
     }
     /// If the provided path corresponds to a page in the project,
     /// return its `PagePath`, else return `None`.
@@ -244,53 +658,221 @@ impl Project {
         }
         None
     }
-    pub fn list_js(&self) -> DdResult<Vec<StaticEntry>> {
-        let mut entries = Vec::new();
-        for module in &self.modules {
-            module.list_js(&mut entries)?;
-        }
-        Ok(entries)
+    /// The JS files to link in the head of pages, as listed when loading the project
+    pub fn list_js(&self) -> &[StaticEntry] {
+        &self.js_entries
     }
-    pub fn list_css(&self) -> DdResult<Vec<StaticEntry>> {
-        let mut entries = Vec::new();
-        for module in &self.modules {
-            module.list_css(&mut entries)?;
-        }
-        Ok(entries)
+    /// Return the bundle to link instead of the JS files, if `bundle-assets` is set
+    pub fn js_bundle(&self) -> Option<&Bundle> {
+        self.js_bundle.as_ref()
+    }
+    /// Return the bundle to link instead of the CSS files, if `bundle-assets` is set
+    pub fn css_bundle(&self) -> Option<&Bundle> {
+        self.css_bundle.as_ref()
+    }
+    /// The CSS files to link in the head of pages, as listed when loading the project
+    pub fn list_css(&self) -> &[StaticEntry] {
+        &self.css_entries
+    }
+    /// The navigation structure of the site
+    pub fn nav(&self) -> &NavIndex {
+        &self.nav
+    }
+    /// Return the list (the site map or one of its sub menus) in which
+    /// the given page is directly listed
+    pub fn section_of(
+        &self,
+        page_path: &PagePath,
+    ) -> Option<&PageList> {
+        let entry = self.nav.get(page_path)?;
+        self.config.site_map.sub_list(&entry.parents)
     }
 
-    pub fn copy_static(
+    /// Build a single page, and print the problems found
+    pub fn build_page(
         &self,
-        dir: &str,
+        page_path: &PagePath,
     ) -> DdResult<()> {
-        let static_src = self.src_path.join(dir);
-        if !static_src.exists() {
-            return Ok(());
+        self.build_pages(std::slice::from_ref(page_path))
+    }
+    /// Build the given pages, and print the problems found
+    pub fn build_pages(
+        &self,
+        page_paths: &[PagePath],
+    ) -> DdResult<()> {
+        let res = self.render_inputs().and_then(|inputs| {
+            for page_path in page_paths {
+                self.render_page(page_path, &inputs)?;
+            }
+            Ok(())
+        });
+        for diagnostic in self.take_page_diagnostics() {
+            diagnostic.print(&self.root);
         }
-        let static_dst = self.build_path.join(dir);
-        copy_normal_recursive(&static_src, &static_dst)?;
-        Ok(())
+        res
     }
-    pub fn build_page(
+    /// Record a problem found while rendering a page
+    pub fn report(
         &self,
         page_path: &PagePath,
-    ) -> DdResult<()> {
+        diagnostic: Diagnostic,
+    ) {
+        let diagnostic = if diagnostic.page.is_none() {
+            diagnostic.in_page(page_path.clone())
+        } else {
+            diagnostic
+        };
+        self.page_diagnostics
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push((page_path.clone(), diagnostic));
+    }
+    /// Record a problem found while rendering a page, located where
+    /// `value` appears in the page or in the configs
+    pub fn report_about(
+        &self,
+        page_path: &PagePath,
+        value: &str,
+        mut diagnostic: Diagnostic,
+    ) {
+        if diagnostic.location.is_none() {
+            diagnostic.location = self.locate_in_page(page_path, value);
+        }
+        self.report(page_path, diagnostic);
+    }
+    /// Return the problems found so far while rendering the given page
+    fn page_diagnostics_of(
+        &self,
+        page_path: &PagePath,
+    ) -> Vec<Diagnostic> {
+        self.page_diagnostics
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .filter(|(path, _)| path == page_path)
+            .map(|(_, diagnostic)| diagnostic.clone())
+            .collect()
+    }
+    /// Return the problems found while rendering pages, in the order of the site map
+    fn take_page_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = std::mem::take(
+            &mut *self
+                .page_diagnostics
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        );
+        // the sort is stable, so a page's problems stay in the order they were found
+        diagnostics
+            .sort_by_key(|(page_path, _)| self.nav.index_of(page_path).unwrap_or(usize::MAX));
+        diagnostics
+            .into_iter()
+            .map(|(_, diagnostic)| diagnostic)
+            .collect()
+    }
+    /// Hash what the HTML of a page depends on, apart from its Markdown:
+    /// the config, the pages of the site map, the static and included files
+    fn render_inputs(&self) -> DdResult<String> {
+        let mut inputs = format!("{:?}\nminify: {}\n", self.config, self.settings.minify);
+        for page_path in self.nav.order() {
+            if let Some(page) = self.pages.get(page_path) {
+                writeln!(
+                    inputs,
+                    "{page_path:?} {:?} {:?} {}",
+                    page.title,
+                    page.description,
+                    page.md_file_path.exists(),
+                )?;
+            }
+        }
+        let mut static_files: Vec<_> = self.static_files()?.into_iter().collect();
+        static_files.sort();
+        let mut include_paths = Vec::new();
+        self.config.body.visit(&mut |element| {
+            if let ElementContent::Include(include) = &element.content
+                && let Some(path) = include.path(&self.src_path)
+            {
+                include_paths.push(path);
+            }
+        });
+        for src_path in static_files.iter().map(|(_, p)| p).chain(&include_paths) {
+            writeln!(
+                inputs,
+                "{src_path:?} {:?}",
+                FileRecord::source_fingerprint(src_path),
+            )?;
+        }
+        Ok(format!("{:016x}", xxh3_64(inputs.as_bytes())))
+    }
+    /// Render a page and write it in the build directory, unless neither its
+    /// Markdown nor the other `inputs` changed since the previous build,
+    /// returning what was rendered, or `None` if the page couldn't be read
+    fn render_page(
+        &self,
+        page_path: &PagePath,
+        inputs: &str,
+    ) -> DdResult<Option<PageReport>> {
+        let start = Instant::now();
         let page = self
             .pages
             .get(page_path)
             .ok_or_else(|| DdError::internal(format!("Page not found: {:?}", page_path)))?;
-        let mut html = String::new();
-        page.write_html(&mut html, self)?;
-        let html_path = page_path.html_path_buf(&self.build_path);
-        if let Some(parent) = html_path.parent() {
-            std::fs::create_dir_all(parent)?;
+        let html_path = page_path.html_path_buf(Path::new(""));
+        let html_path = html_path.to_string_lossy();
+        let unchanged = FileRecord::source_fingerprint(&page.md_file_path).and_then(|source| {
+            self.manifest()
+                .unchanged_page(self.sink(), &html_path, &source, inputs)
+        });
+        let page_record = match unchanged {
+            Some(page_record) => {
+                for diagnostic in &page_record.diagnostics {
+                    self.report(page_path, diagnostic.clone());
+                }
+                page_record
+            }
+            None => {
+                let Some(page_writer) = page.page_writer(self)? else {
+                    return Ok(None);
+                };
+                let mut html = String::new();
+                page_writer.write_html(&mut html)?;
+                if self.settings.minify {
+                    html = minify_html(&html);
+                }
+                let search_sections = if self.has_search_index() {
+                    page_writer.search_sections().to_vec()
+                } else {
+                    Vec::new()
+                };
+                let page_record = PageRecord {
+                    size: html.len(),
+                    heading_count: page_writer.heading_count(),
+                    links: page_writer.links().to_vec(),
+                    diagnostics: self.page_diagnostics_of(page_path),
+                    search_sections,
+                };
+                let record = FileRecord::generated(html.as_bytes())
+                    .with_source(&page.md_file_path)
+                    .with_page(inputs, page_record.clone());
+                self.write_to_build(&html_path, html.as_bytes(), record)?;
+                page_record
+            }
+        };
+        if self.config.llms_txt {
+            self.copy_to_build(&Self::page_md_copy_path(page_path), &page.md_file_path)?;
         }
-        if html_path.exists() {
-            fs::remove_file(&html_path)?;
+        if self.has_search_index() {
+            self.page_search_sections()
+                .insert(page_path.clone(), page_record.search_sections);
         }
-        let mut file = fs::File::create(&html_path)?;
-        file.write_all(html.as_bytes())?;
-        Ok(())
+        Ok(Some(PageReport {
+            path: page_path.clone(),
+            file: html_path.to_string(),
+            title: page.title.clone(),
+            size: page_record.size,
+            render_time: start.elapsed(),
+            heading_count: page_record.heading_count,
+            links: page_record.links,
+        }))
     }
     pub fn check_img_path(
         &self,
@@ -305,56 +887,70 @@ impl Project {
                 img_path = &img_path[3..];
             }
         }
-        let path = self.build_path.join(img_path);
-        if !path.exists() {
-            eprintln!(
-                "{}: {} contains a broken img src: {}",
-                "error".red().bold(),
-                page_path.to_string().yellow(),
-                img_path.to_string().red(),
+        if !self.sink().exists(self.fingerprints.resolve(img_path)) {
+            self.report_about(
+                page_path,
+                img_path,
+                Diagnostic::error(format!("broken image: {img_path}")),
             );
         }
     }
+    /// Replace a `--name` reference to a variable of the config with
+    /// its value, leaving other values untouched
+    pub fn expand_var<'s>(
+        &self,
+        src: &'s str,
+    ) -> Cow<'s, str> {
+        match src
+            .strip_prefix("--")
+            .and_then(|name| self.config.var(name))
+        {
+            Some(value) => Cow::Owned(value),
+            None => Cow::Borrowed(src),
+        }
+    }
+    /// Return the path of the file of the `img` directory an image source
+    /// (eg `../img/logo.png`, or `--logo` with a `logo` variable) refers
+    /// to, eg `img/logo.png`, with the count of its leading `../`
+    pub fn img_src_path(
+        &self,
+        src: &str,
+    ) -> Option<(usize, String)> {
+        let src = self.expand_var(src);
+        let (_, up, path) = regex_captures!(r"^((?:\.\./)*)(img/.*)$", &src)?;
+        Some((up.len() / 3, path.to_string()))
+    }
     pub fn img_url(
         &self,
         src: &str,
         page_path: &PagePath,
     ) -> String {
-        let mut src = src;
-        // conf var expansions
-        let conf_var_value;
-        if let Some(var_name) = src.strip_prefix("--") {
-            if let Some(var_value) = self.config.var(var_name) {
-                conf_var_value = var_value;
-                src = &conf_var_value;
-            }
-        }
         // filtering to change only relative links to /img files
-        if let Some((_, before, path)) = regex_captures!(r"^(\.\./)*(img/.*)$", &src) {
-            self.check_img_path(src, page_path);
-            let depth = page_path.depth();
-            if depth == 0 && before.is_empty() {
-                return src.to_string(); // no rewriting needed, it's already correct
-            }
-            let mut url = String::new();
-            for _ in 0..depth {
-                url.push_str("../");
-            }
-            url.push_str(path);
-            return url;
+        let Some((up, path)) = self.img_src_path(src) else {
+            return self.expand_var(src).into_owned();
+        };
+        self.check_img_path(&self.expand_var(src), page_path);
+        let path = self.fingerprints.resolve(&path);
+        let depth = page_path.depth();
+        if depth == 0 && up == 0 {
+            return path.to_string(); // no other rewriting needed
+        }
+        let mut url = String::new();
+        for _ in 0..depth {
+            url.push_str("../");
         }
-        src.to_string()
+        url.push_str(path);
+        url
     }
     pub fn load_file(
         &self,
         path: &str,
     ) -> DdResult<Option<String>> {
-        let file_path = self.build_path.join(path);
-        if !file_path.exists() {
+        let Some(bytes) = self.sink().read(self.fingerprints.resolve(path))? else {
             return Ok(None);
-        }
-        let content = fs::read_to_string(file_path)?;
-        Ok(Some(content))
+        };
+        let content = std::str::from_utf8(&bytes)?;
+        Ok(Some(content.to_string()))
     }
     /// Check if the given `PagePath` exists in the project,
     /// write an error if it does not.
@@ -371,8 +967,7 @@ impl Project {
         &self,
         current_page: &PagePath,
     ) -> Option<&Page> {
-        self.config
-            .site_map
+        self.nav
             .previous(current_page)
             .and_then(|p| self.pages.get(p))
     }
@@ -380,12 +975,28 @@ impl Project {
         &self,
         current_page: &PagePath,
     ) -> Option<&Page> {
-        self.config
-            .site_map
-            .next(current_page)
-            .and_then(|p| self.pages.get(p))
+        self.nav.next(current_page).and_then(|p| self.pages.get(p))
     }
 
+    /// Expand the `--name` of a link, or return `None` when no expansion
+    /// is possible
+    pub fn expand_link_var(
+        &self,
+        var_name: &str,
+        page_path: &PagePath,
+    ) -> Option<LinkVar<'_>> {
+        // conf var expansions, they have priority as they may overload
+        // dynamic expansions
+        if let Some(var_value) = self.config.var(var_name) {
+            return Some(LinkVar::Value(var_value));
+        }
+        // dynamic expansions
+        match var_name {
+            "previous" => self.nav.previous(page_path).map(LinkVar::Page),
+            "next" => self.nav.next(page_path).map(LinkVar::Page),
+            _ => before_0_16::expand_special_var(var_name, &self.config).map(LinkVar::Value),
+        }
+    }
     /// Return the modified link URL.
     /// return `None` when no expansion is possible, which should
     /// lead to the container being skipped.
@@ -395,76 +1006,68 @@ impl Project {
         page_path: &PagePath,
     ) -> Option<String> {
         if let Some(var_name) = src.strip_prefix("--") {
-            // conf var expansions, they have priority as they may overload
-            // dynamic expansions
-            if let Some(var_value) = self.config.var(var_name) {
-                return Some(var_value);
-            }
-            // dynamic expansions
-            if var_name == "previous" {
-                return self
-                    .config
-                    .site_map
-                    .previous(page_path)
-                    .map(|dst_page_path| page_path.link_to(dst_page_path));
-            }
-            if var_name == "next" {
-                return self
-                    .config
-                    .site_map
-                    .next(page_path)
-                    .map(|dst_page_path| page_path.link_to(dst_page_path));
-            }
-            if let Some(value) = before_0_16::expand_special_var(var_name, &self.config) {
-                return Some(value);
-            }
-            return None; // this way the container might be skipped
+            // None: this way the container might be skipped
+            return match self.expand_link_var(var_name, page_path)? {
+                LinkVar::Value(value) => Some(value),
+                LinkVar::Page(dst_page_path) => Some(page_path.link_to(dst_page_path)),
+            };
         }
         // FIXME rewrite absolute internal links coming from var expansions,
         // which may be in the form /path/to/page or /path/to/page.md
         // (this my require refactor to always return a string)
 
-        // rewrite absolute internal links, making them relative to the current page
+        // rewrite internal links to .md files, making them relative to the current page
+        let (dst_page_path, url) = self.resolve_internal_link(src, page_path)?;
+        if !self.pages.contains_key(&dst_page_path) {
+            let message = if src.starts_with('/') {
+                format!("broken link: {src}")
+            } else {
+                format!("broken relative link: {src}")
+            };
+            self.report_about(page_path, src, Diagnostic::error(message));
+        }
+        Some(url)
+    }
+    /// If the link is an internal link to a page, either absolute like
+    /// `/guide/setup.md` or relative like `../setup.md`, return the path of
+    /// the target page (which may not exist).
+    pub fn internal_link_target(
+        &self,
+        src: &str,
+        page_path: &PagePath,
+    ) -> Option<PagePath> {
+        self.resolve_internal_link(src, page_path)
+            .map(|(dst_page_path, _)| dst_page_path)
+    }
+    /// If the link is an internal link to a page, return the path of the
+    /// target page and the URL of this page relative to the current one.
+    ///
+    /// This is the only place where internal links are recognized, so that
+    /// the build and the check always agree on their targets.
+    fn resolve_internal_link(
+        &self,
+        src: &str,
+        page_path: &PagePath,
+    ) -> Option<(PagePath, String)> {
+        if src.starts_with("--") {
+            return None;
+        }
         if let Some((_, path, file, _ext, hash)) =
             regex_captures!(r"^/([\w\-/]+/)*([\w\-/]*?)(?:index)?(\.md)?/?(#.*)?$", &src,)
         {
-            let depth = page_path.depth();
-            let mut url = String::new();
-            for _ in 0..depth {
-                url.push_str("../");
-            }
+            let mut url = "../".repeat(page_path.depth());
             url.push_str(path);
             url.push_str(file);
             url.push_str(hash);
-            let dst_page_path = PagePath::from_path_file(path, file);
-            if !self.pages.contains_key(&dst_page_path) {
-                eprintln!("path: {}, file: {}", path, file);
-                eprintln!("dst_page_path: {:?}", dst_page_path);
-                eprintln!(
-                    "{}: {} contains a broken link: {}",
-                    "error".red(),
-                    page_path.to_string().yellow(),
-                    src.to_string().red(),
-                );
-            }
-            return Some(url);
+            return Some((PagePath::from_path_file(path, file), url));
         }
-        // rewrite relative internal links to .md files
         if let Some((_, path, file, _ext, hash)) =
             regex_captures!(r"^(\.\./|[\w\-/]+/)*([\w\-/]+?)(\.md)?/?(#.*)?$", &src,)
         {
             let dst_page_path = page_path.follow_relative_link(path, file);
-            if !self.pages.contains_key(&dst_page_path) {
-                eprintln!(
-                    "{}: {} contains a broken relative link: {}",
-                    "error".red().bold(),
-                    page_path.to_string().yellow(),
-                    src.to_string().red(),
-                );
-            }
             let file = if file == "index" { "" } else { file };
             let url = format!("{}{}{}", path, file, hash,);
-            return Some(url);
+            return Some((dst_page_path, url));
         }
         None
     }
@@ -482,6 +1085,14 @@ impl Project {
             None => Cow::Borrowed(src),
         }
     }
+    /// Return the path of an asset in the build directory, which is the
+    /// fingerprinted one when `fingerprint-assets` is set
+    pub fn asset_path<'s>(
+        &'s self,
+        path: &'s str,
+    ) -> &'s str {
+        self.fingerprints.resolve(path)
+    }
     pub fn static_url(
         &self,
         filename: &str,
@@ -496,3 +1107,86 @@ impl Project {
         url
     }
 }
+
+/// What the `--name` of a link expands to
+pub enum LinkVar<'p> {
+    /// The value of a variable
+    Value(String),
+    /// The page of a dynamic expansion, like `--previous`
+    Page(&'p PagePath),
+}
+
+/// Check the build directory doesn't hold the project or its sources,
+/// which would be overwritten by the build, and isn't inside the sources,
+/// where the build output would be taken for sources
+fn check_build_dir(
+    root: &Path,
+    src_path: &Path,
+    build_path: &Path,
+) -> DdResult<()> {
+    let build = normalize_path(build_path);
+    for (dir, name) in [(root, "project"), (src_path, "src")] {
+        let dir = normalize_path(dir);
+        let reason = if build == dir {
+            format!("it's the {name} directory")
+        } else if dir.starts_with(&build) {
+            format!("it contains the {name} directory")
+        } else {
+            continue;
+        };
+        return Err(DdError::InvalidBuildDir {
+            path: build_path.to_path_buf(),
+            reason,
+        });
+    }
+    if build.starts_with(normalize_path(src_path)) {
+        return Err(DdError::InvalidBuildDir {
+            path: build_path.to_path_buf(),
+            reason: "it's inside the src directory".to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[test]
+fn test_check_build_dir() {
+    let root = Path::new("/doc");
+    let src = Path::new("/doc/src");
+    assert!(check_build_dir(root, src, Path::new("/doc/site")).is_ok());
+    assert!(check_build_dir(root, src, Path::new("/tmp/preview")).is_ok());
+    assert!(check_build_dir(root, src, Path::new("/doc/.")).is_err());
+    assert!(check_build_dir(root, src, Path::new("/doc/src/../src")).is_err());
+    assert!(check_build_dir(root, src, Path::new("/doc/src/out")).is_err());
+    assert!(check_build_dir(root, src, Path::new("/")).is_err());
+}
+
+#[test]
+fn test_unchanged_pages_not_rendered() {
+    // built in the file system, as the manifest is read from the build directory
+    let test_project = TestProject::new("unchanged");
+    let root = &test_project.root;
+    test_project
+        .write(
+            "ddoc.hjson",
+            "title: Test\nsite-map: {\n    Home: index.md\n    Other: other.md\n}\n",
+        )
+        .write("src/index.md", "# Home\n\n![missing](img/missing.png)\n")
+        .write("src/other.md", "# Other\n");
+    let result = Project::load(root).unwrap().build().unwrap();
+    assert_eq!(result.diagnostics.len(), 1);
+    // a page which isn't rendered again isn't written again
+    let index_path = root.join("site/index.html");
+    fs::write(&index_path, "untouched").unwrap();
+    let result = Project::load(root).unwrap().build().unwrap();
+    assert_eq!(fs::read_to_string(&index_path).unwrap(), "untouched");
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.pages.len(), 2);
+    assert_eq!(result.pages[0].heading_count, 1);
+    // but a change of the config makes all pages rendered again
+    test_project.write(
+        "ddoc.hjson",
+        "title: Changed\nsite-map: {\n    Home: index.md\n    Other: other.md\n}\n",
+    );
+    Project::load(root).unwrap().build().unwrap();
+    assert!(fs::read_to_string(&index_path).unwrap().contains("Changed"));
+}
@@ -0,0 +1,161 @@
+use {
+    crate::*,
+    std::path::PathBuf,
+};
+
+/// Directories given at launch (eg with `--src` and `--out`), which take
+/// precedence over the ones of the config
+#[derive(Debug, Default, Clone)]
+pub struct DirOverrides {
+    pub src: Option<PathBuf>,
+    pub build: Option<PathBuf>,
+}
+
+/// A builder of `Project`, for when the defaults (config read in the
+/// project directory, site written on disk) don't fit, eg when embedding ddoc
+///
+/// ```no_run
+/// use {ddoc::*, std::sync::Arc};
+///
+/// let sink = Arc::new(MemorySink::default());
+/// let project = ProjectBuilder::new("website")
+///     .src_dir("content")
+///     .sink(Arc::clone(&sink))
+///     .load()?;
+/// let result = project.build()?;
+/// for path in &result.files {
+///     println!("{path}: {} bytes", sink.get(path).unwrap_or_default().len());
+/// }
+/// # Ok::<(), DdError>(())
+/// ```
+pub struct ProjectBuilder {
+    root: PathBuf,
+    config: Option<Config>,
+    dir_overrides: DirOverrides,
+    sink: Option<Box<dyn OutputSink>>,
+    minify: bool,
+}
+
+impl ProjectBuilder {
+    /// Start building the project whose root is at `root`
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            config: None,
+            dir_overrides: DirOverrides::default(),
+            sink: None,
+            minify: false,
+        }
+    }
+    /// Use this config instead of reading the config file of the project,
+    /// which isn't even looked for (so it may be absent or invalid)
+    pub fn config(
+        mut self,
+        config: Config,
+    ) -> Self {
+        self.config = Some(config);
+        self
+    }
+    /// Set the directory of the pages and static files, relative to
+    /// the root of the project if it's not absolute
+    pub fn src_dir<P: Into<PathBuf>>(
+        mut self,
+        path: P,
+    ) -> Self {
+        self.dir_overrides.src = Some(path.into());
+        self
+    }
+    /// Set the directory where the site is built, relative to the root
+    /// of the project if it's not absolute
+    ///
+    /// This is ignored when a custom sink is given.
+    pub fn build_dir<P: Into<PathBuf>>(
+        mut self,
+        path: P,
+    ) -> Self {
+        self.dir_overrides.build = Some(path.into());
+        self
+    }
+    pub fn dirs(
+        mut self,
+        dir_overrides: DirOverrides,
+    ) -> Self {
+        self.dir_overrides = dir_overrides;
+        self
+    }
+    /// Send the files of the site to this sink instead of writing them
+    /// in the build directory
+    pub fn sink<S: OutputSink + 'static>(
+        mut self,
+        sink: S,
+    ) -> Self {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+    /// Minify the HTML pages and the CSS and JS files
+    pub fn minify(
+        mut self,
+        minify: bool,
+    ) -> Self {
+        self.minify = minify;
+        self
+    }
+    /// Load the project
+    ///
+    /// # Errors
+    /// Return `DdError::ConfigNotFound` if no config was given and there's
+    /// no config file, or other errors if the project can't be read
+    pub fn load(self) -> DdResult<Project> {
+        Project::load_with(
+            self.root,
+            ProjectSettings {
+                config: self.config,
+                dir_overrides: self.dir_overrides,
+                sink: self.sink,
+                minify: self.minify,
+            },
+        )
+    }
+}
+
+/// What a `ProjectBuilder` may change in the loading and building of a project
+#[derive(Default)]
+pub struct ProjectSettings {
+    pub config: Option<Config>,
+    pub dir_overrides: DirOverrides,
+    pub sink: Option<Box<dyn OutputSink>>,
+    pub minify: bool,
+}
+
+#[test]
+fn test_build_in_memory() {
+    let test_project = TestProject::new("memory");
+    test_project.write("pages/index.md", "# Hello\n\n![missing](img/missing.png)\n");
+    // the config file of the project isn't read when a config is given
+    test_project.write("ddoc.hjson", "not: [valid");
+    let project = test_project
+        .builder(
+            r#"{
+                title: Test
+                site-map: {
+                    Home: index.md
+                }
+            }"#,
+        )
+        .src_dir("pages")
+        .load()
+        .unwrap();
+    let result = project.build().unwrap();
+    assert!(result.files.contains(&"index.html".to_string()));
+    let html = test_project.output("index.html");
+    assert!(html.contains("<title>Home - Test</title>"));
+    assert!(html.contains("Hello</h1>"));
+    assert_eq!(result.diagnostics.len(), 1);
+    assert!(result.has_errors());
+    assert_eq!(result.pages.len(), 1);
+    assert_eq!(result.pages[0].file, "index.html");
+    assert_eq!(result.pages[0].heading_count, 1);
+    let report = result.report_json(&test_project.root).unwrap();
+    assert!(report.contains(r#""path": "pages/index.md""#));
+    assert!(!test_project.root.join("site").exists());
+}
@@ -0,0 +1,171 @@
+use {
+    crate::*,
+    pulldown_cmark::{
+        Event,
+        Tag,
+        TagEnd,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// Name of the index of the text of the pages, built when the `search`
+/// plugin is active
+pub static SEARCH_INDEX_FILE_NAME: &str = "search-index.json";
+
+/// Name of the plugin using the search index
+static SEARCH_PLUGIN_NAME: &str = "search";
+
+/// A part of a page, starting at a heading, or the text before the first
+/// heading, for the search index
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchSection {
+    /// ID of the heading, to link to the section
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Level of the heading, from 1 to 6, or 0 when there's no heading
+    pub level: u8,
+    pub title: String,
+    /// The text of the section, with whitespace collapsed
+    pub text: String,
+}
+
+/// What's written in the search index
+#[derive(Serialize)]
+struct SearchIndex<'p> {
+    pages: Vec<SearchIndexPage<'p>>,
+}
+
+#[derive(Serialize)]
+struct SearchIndexPage<'p> {
+    title: &'p str,
+    /// URL of the page, relative to the site root
+    href: String,
+    sections: &'p [SearchSection],
+}
+
+/// Split the text of Markdown events in sections starting at headings
+///
+/// Headings must already have their ID. Raw HTML and front matter aren't indexed.
+pub fn search_sections(events: &[Event]) -> Vec<SearchSection> {
+    let mut sections = vec![SearchSection::default()];
+    let mut in_heading = false;
+    let mut in_metadata = false;
+    for (i, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(Tag::MetadataBlock(_)) => {
+                in_metadata = true;
+            }
+            Event::End(TagEnd::MetadataBlock(_)) => {
+                in_metadata = false;
+            }
+            _ if in_metadata => {}
+            Event::Start(Tag::Heading { level, id, .. }) => {
+                in_heading = true;
+                sections.push(SearchSection {
+                    id: id.as_ref().map(ToString::to_string),
+                    level: *level as u8,
+                    title: heading_text(&events[i + 1..]),
+                    text: String::new(),
+                });
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                in_heading = false;
+            }
+            Event::Text(s) | Event::Code(s) if !in_heading => {
+                if let Some(section) = sections.last_mut() {
+                    section.text.push_str(s);
+                }
+            }
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Item
+                | TagEnd::TableCell
+                | TagEnd::CodeBlock
+                | TagEnd::BlockQuote(_),
+            ) => {
+                if let Some(section) = sections.last_mut() {
+                    section.text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    for section in &mut sections {
+        section.text = section
+            .text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    if sections[0].text.is_empty() {
+        sections.remove(0);
+    }
+    sections
+}
+
+impl Project {
+    /// Tell whether the search index must be built, which is the case
+    /// when the `search` plugin is active
+    pub fn has_search_index(&self) -> bool {
+        self.plugin_names().any(|name| name == SEARCH_PLUGIN_NAME)
+    }
+
+    /// Build the JSON of the search index, with the sections of the
+    /// rendered pages, in the order of the site map
+    ///
+    /// # Errors
+    /// Return `DdError::Json` if the serialization fails
+    pub fn search_index_json(&self) -> DdResult<String> {
+        let search_sections = self.page_search_sections();
+        let mut pages = Vec::new();
+        for page_path in self.nav().order() {
+            let (Some(page), Some(sections)) =
+                (self.pages.get(page_path), search_sections.get(page_path))
+            else {
+                continue;
+            };
+            pages.push(SearchIndexPage {
+                title: &page.title,
+                href: page_path
+                    .to_absolute_url("")
+                    .trim_start_matches('/')
+                    .to_string(),
+                sections,
+            });
+        }
+        Ok(serde_json::to_string(&SearchIndex { pages })?)
+    }
+}
+
+#[test]
+fn test_search_sections() {
+    use pulldown_cmark::Parser;
+    let md =
+        "Intro *text*\n\n# Install {#install}\n\nRun `cargo install`\nnow.\n\n## Use\n\n- a\n- b\n";
+    let events = Parser::new_ext(md, pulldown_cmark::Options::all()).collect::<Vec<_>>();
+    let sections = search_sections(&events);
+    assert_eq!(sections.len(), 3);
+    assert_eq!(sections[0].text, "Intro text");
+    assert_eq!(sections[1].id.as_deref(), Some("install"));
+    assert_eq!(sections[1].level, 1);
+    assert_eq!(sections[1].title, "Install");
+    assert_eq!(sections[1].text, "Run cargo install now.");
+    assert_eq!(sections[2].id, None);
+    assert_eq!(sections[2].text, "a b");
+}
+
+#[test]
+fn test_search_sections_skip_front_matter() {
+    use pulldown_cmark::Parser;
+    let md = "---\ndescription: secret text\n---\n\n# Title\n\nBody\n";
+    let events = Parser::new_ext(md, pulldown_cmark::Options::all()).collect::<Vec<_>>();
+    let sections = search_sections(&events);
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].title, "Title");
+    assert_eq!(sections[0].text, "Body");
+}
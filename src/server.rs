@@ -1,13 +1,21 @@
 //! nothing yet
 use {
     crate::*,
-    rouille::Response,
-    std::path::PathBuf,
+    rouille::{
+        Request,
+        Response,
+    },
+    std::path::{
+        Path,
+        PathBuf,
+    },
 };
 
 pub struct Server {
     base_url: String,
     addr: String,
+    /// The path under which the site is served, starting and ending with a `/`
+    base_path: String,
     static_path: PathBuf,
 }
 
@@ -15,12 +23,14 @@ impl Server {
     pub fn new(
         static_path: PathBuf,
         port: u16,
+        base_path: String,
     ) -> DdResult<Self> {
         let addr = format!("localhost:{port}");
-        let base_url = format!("http://{addr}/");
+        let base_url = format!("http://{addr}{base_path}");
         Ok(Self {
             base_url,
             addr,
+            base_path,
             static_path,
         })
     }
@@ -29,33 +39,58 @@ impl Server {
     }
     pub fn run(self) -> DdResult<()> {
         let static_path = self.static_path;
+        let base_path = self.base_path;
         let rouille_server = rouille::Server::new(self.addr, move |request| {
-            // build the file path
-            let mut path = static_path.clone();
-            path.push(&request.url()[1..]); // Remove leading /
-
-            if path.is_dir() {
-                if request.url().ends_with('/') {
-                    // If it's a directory with trailing /,
-                    // the URL is correct but we must send index.html
-                    path.push("index.html");
-                    if path.exists() {
-                        if let Ok(file) = std::fs::File::open(&path) {
-                            return Response::from_file("text/html", file);
-                        }
-                    }
-                } else {
-                    // The URL is missing a trailing /
-                    let new_url = format!("{}/", request.url());
-                    return Response::redirect_301(new_url);
+            if base_path == "/" {
+                return serve_file(request, &request.url(), &static_path);
+            }
+            // The site is mounted under the base path, as in production
+            let prefix = base_path.trim_end_matches('/');
+            let url = request.url();
+            if url == "/" || url == prefix {
+                return Response::redirect_301(base_path.clone());
+            }
+            match request.remove_prefix(prefix) {
+                Some(sub_request) if sub_request.url().starts_with('/') => {
+                    serve_file(&sub_request, &url, &static_path)
                 }
+                _ => Response::empty_404(),
             }
-
-            // Try to serve the file
-            rouille::match_assets(request, &static_path)
         })
         .map_err(|e| DdError::Server(e.to_string()))?;
         rouille_server.run();
         Ok(())
     }
 }
+
+/// Serve the file of the request, whose URL is relative to the site's root,
+/// `full_url` being the URL before the removal of the base path
+fn serve_file(
+    request: &Request,
+    full_url: &str,
+    static_path: &Path,
+) -> Response {
+    // build the file path
+    let mut path = static_path.to_path_buf();
+    path.push(&request.url()[1..]); // Remove leading /
+
+    if path.is_dir() {
+        if request.url().ends_with('/') {
+            // If it's a directory with trailing /,
+            // the URL is correct but we must send index.html
+            path.push("index.html");
+            if path.exists()
+                && let Ok(file) = std::fs::File::open(&path)
+            {
+                return Response::from_file("text/html", file);
+            }
+        } else {
+            // The URL is missing a trailing /
+            let new_url = format!("{full_url}/");
+            return Response::redirect_301(new_url);
+        }
+    }
+
+    // Try to serve the file
+    rouille::match_assets(request, static_path)
+}
@@ -30,12 +30,14 @@ impl StaticEntry {
     ///
     /// If a file with the same `served_path` already exists in `entries`, the
     /// previous one is kept and the new one is skipped, as the plugin entry
-    /// is overridden by the main project entry.
+    /// is overridden by the main project entry. A warning is then added
+    /// to `diagnostics`.
     pub fn list_in(
         dir: &Path,
         serving_prefix: &str,
         ext_filter: Option<&str>,
         entries: &mut Vec<StaticEntry>,
+        diagnostics: &mut Vec<Diagnostic>,
     ) -> DdResult<()> {
         if !dir.exists() {
             return Ok(());
@@ -66,10 +68,10 @@ impl StaticEntry {
                 if file_name.starts_with('.') {
                     continue;
                 }
-                if let Some(ext) = ext_filter {
-                    if !file_name.ends_with(ext) {
-                        continue;
-                    }
+                if let Some(ext) = ext_filter
+                    && !file_name.ends_with(ext)
+                {
+                    continue;
                 }
                 let Some(mtime) = entry.metadata().and_then(|m| m.modified()).ok() else {
                     continue;
@@ -80,11 +82,14 @@ impl StaticEntry {
                     .as_secs();
                 let relative = src_path.strip_prefix(dir).unwrap_or(&src_path);
                 let served_path = format!("{}{}", serving_prefix, relative.to_string_lossy());
-                if entries.iter().any(|e| e.served_path == served_path) {
-                    eprintln!(
-                        "Warning: duplicate static entry for {}, skipping {}",
-                        served_path,
-                        src_path.display()
+                if let Some(kept) = entries.iter().find(|e| e.served_path == served_path) {
+                    diagnostics.push(
+                        Diagnostic::warning(format!("duplicate static entry: {served_path}"))
+                            .at(Location::new(&src_path))
+                            .with_help(format!(
+                                "this file is skipped, {} is used",
+                                kept.src_path.display()
+                            )),
                     );
                     continue;
                 }
@@ -0,0 +1,73 @@
+use {
+    crate::*,
+    std::{
+        fs,
+        path::PathBuf,
+        sync::Arc,
+    },
+};
+
+/// A project in a temporary directory, for tests, built in memory.
+///
+/// The directory is removed on drop, even when the test panics.
+pub struct TestProject {
+    pub root: PathBuf,
+    pub sink: Arc<MemorySink>,
+}
+
+impl TestProject {
+    /// Make an empty project directory, whose name must be unique
+    /// among the tests, as they run in parallel
+    pub fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("ddoc-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        Self {
+            root,
+            sink: Arc::new(MemorySink::default()),
+        }
+    }
+    /// Write a file, given by its path relative to the project root,
+    /// creating its parent directories
+    pub fn write(
+        &self,
+        path: &str,
+        content: impl AsRef<[u8]>,
+    ) -> &Self {
+        let path = self.root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+        self
+    }
+    /// Return a builder of the project, with the given Hjson config,
+    /// writing in the memory sink
+    pub fn builder(
+        &self,
+        hjson_config: &str,
+    ) -> ProjectBuilder {
+        let config: Config = deser_hjson::from_str(hjson_config).unwrap();
+        ProjectBuilder::new(&self.root)
+            .config(config)
+            .sink(Arc::clone(&self.sink))
+    }
+    /// Load the project, with the given Hjson config, writing in the memory sink
+    pub fn load(
+        &self,
+        hjson_config: &str,
+    ) -> Project {
+        self.builder(hjson_config).load().unwrap()
+    }
+    /// Return the content of a file written in the memory sink
+    pub fn output(
+        &self,
+        path: &str,
+    ) -> String {
+        self.sink.get_string(path).unwrap()
+    }
+}
+
+impl Drop for TestProject {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
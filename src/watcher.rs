@@ -45,6 +45,9 @@ pub enum FileChange {
 pub struct WatchTarget {
     pub path: PathBuf,
     pub recursive: bool, // ie directory or not
+    /// Whether only the changes of config files (ddoc.hjson, etc.) of the
+    /// directory matter
+    pub config_files_only: bool,
 }
 
 impl WatchTarget {
@@ -52,12 +55,23 @@ impl WatchTarget {
         Self {
             path: path.into(),
             recursive: true,
+            config_files_only: false,
         }
     }
     pub fn new_file<P: Into<PathBuf>>(path: P) -> Self {
         Self {
             path: path.into(),
             recursive: false,
+            config_files_only: false,
+        }
+    }
+    /// Watch the config files directly in the given directory, whether
+    /// they exist or not yet
+    pub fn new_config_dir<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            recursive: false,
+            config_files_only: true,
         }
     }
 }
@@ -70,6 +84,26 @@ pub fn rebuild_on_change(
     mut project: Project,
     base_url: String, // to display the modified page URL
 ) -> Result<RecommendedWatcher, notify::Error> {
+    let targets = project.watch_targets();
+    // a change in a directory watched for its config files matters only
+    // if it's about a config file, or in another watched directory
+    let config_dirs: Vec<PathBuf> = targets
+        .iter()
+        .filter(|target| target.config_files_only)
+        .map(|target| target.path.clone())
+        .collect();
+    let dirs: Vec<PathBuf> = targets
+        .iter()
+        .filter(|target| target.recursive)
+        .map(|target| target.path.clone())
+        .collect();
+    let is_relevant = move |path: &PathBuf| {
+        Config::is_config_file(path)
+            || !path
+                .parent()
+                .is_some_and(|dir| config_dirs.iter().any(|d| d == dir))
+            || dirs.iter().any(|dir| path.starts_with(dir))
+    };
     let skip = Arc::new(AtomicBool::new(false));
     let snd_skip = skip.clone();
     //let (snd, rcv) = mpsc::sync_channel::<FileChange>(100);
@@ -114,6 +148,9 @@ pub fn rebuild_on_change(
                         return;
                     }
                 }
+                if !we.paths.is_empty() && !we.paths.iter().any(&is_relevant) {
+                    return;
+                }
                 if snd_skip.load(Ordering::SeqCst) {
                     debug!("skipping event due to skip flag: {we:?}");
                     return;
@@ -133,7 +170,7 @@ pub fn rebuild_on_change(
             }
             Err(e) => warn!("watch error: {e:?}"),
         })?;
-    for target in project.watch_targets() {
+    for target in targets {
         watcher.watch(
             &target.path,
             if target.recursive {